@@ -0,0 +1,24 @@
+#include <pthread.h>
+
+#define N 1
+
+int n[N] = { 0 };
+pthread_rwlock_t l[N] = { PTHREAD_RWLOCK_INITIALIZER };
+
+void f1() {
+    int x;
+
+    for (int i = 0; i < N; i++) {
+        pthread_rwlock_rdlock(&l[i]);
+        x = n[i];
+        pthread_rwlock_unlock(&l[i]);
+
+        x++;
+
+        pthread_rwlock_wrlock(&l[i]);
+        n[i] += x;
+        pthread_rwlock_unlock(&l[i]);
+    }
+}
+
+int main() {}
@@ -0,0 +1,29 @@
+#include <pthread.h>
+
+int n = 0;
+pthread_mutex_t m = PTHREAD_MUTEX_INITIALIZER;
+
+void f1() {
+    if (pthread_mutex_trylock(&m) == 0) {
+        n++;
+        pthread_mutex_unlock(&m);
+    }
+}
+
+void f2() {
+    if (pthread_mutex_trylock(&m) != 0) {
+        return;
+    }
+    n++;
+    pthread_mutex_unlock(&m);
+}
+
+void f3() {
+    int r = pthread_mutex_trylock(&m);
+    if (r == 0) {
+        n++;
+        pthread_mutex_unlock(&m);
+    }
+}
+
+int main() {}
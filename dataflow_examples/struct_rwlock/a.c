@@ -0,0 +1,46 @@
+#include <pthread.h>
+
+typedef struct {
+    int n;
+    pthread_rwlock_t l;
+} ss;
+
+ss s = { 0, PTHREAD_RWLOCK_INITIALIZER };
+
+void rdlock() {
+    pthread_rwlock_rdlock(&s.l);
+}
+
+void wrlock() {
+    pthread_rwlock_wrlock(&s.l);
+}
+
+void unlock() {
+    pthread_rwlock_unlock(&s.l);
+}
+
+void f1() {
+    int x;
+
+    pthread_rwlock_rdlock(&s.l);
+    x = s.n;
+    pthread_rwlock_unlock(&s.l);
+
+    x++;
+
+    pthread_rwlock_wrlock(&s.l);
+    s.n += x;
+    pthread_rwlock_unlock(&s.l);
+
+    rdlock();
+    x = s.n;
+    unlock();
+
+    x++;
+
+    wrlock();
+    s.n += x;
+    unlock();
+}
+
+int main() {}
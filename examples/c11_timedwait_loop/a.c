@@ -0,0 +1,39 @@
+#include <threads.h>
+#include <time.h>
+
+int ready = 0;
+
+mtx_t m;
+cnd_t c;
+
+int t_fun(void *arg) {
+    mtx_lock(&m);
+    ready = 1;
+    cnd_signal(&c);
+    mtx_unlock(&m);
+    return 0;
+}
+
+int main() {
+    thrd_t id;
+    struct timespec ts;
+    int rc = 0;
+    mtx_init(&m, mtx_plain);
+    cnd_init(&c);
+    thrd_create(&id, t_fun, NULL);
+
+    timespec_get(&ts, TIME_UTC);
+    ts.tv_sec += (time_t) 1;
+    mtx_lock(&m);
+    while (ready == 0) {
+        rc = cnd_timedwait(&c, &m, &ts);
+        if (rc == thrd_timedout) {
+            break;
+        }
+    }
+    mtx_unlock(&m);
+
+    thrd_join(id, NULL);
+    mtx_destroy(&m);
+    cnd_destroy(&c);
+}
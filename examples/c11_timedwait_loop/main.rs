@@ -0,0 +1,105 @@
+use ::libc;
+extern "C" {
+    fn thrd_create(
+        __thr: *mut thrd_t,
+        __func: thrd_start_t,
+        __arg: *mut libc::c_void,
+    ) -> libc::c_int;
+    fn thrd_join(__thr: thrd_t, __res: *mut libc::c_int) -> libc::c_int;
+    fn mtx_init(__mutex: *mut mtx_t, __type: libc::c_int) -> libc::c_int;
+    fn mtx_lock(__mutex: *mut mtx_t) -> libc::c_int;
+    fn mtx_unlock(__mutex: *mut mtx_t) -> libc::c_int;
+    fn mtx_destroy(__mutex: *mut mtx_t);
+    fn cnd_init(__cond: *mut cnd_t) -> libc::c_int;
+    fn cnd_signal(__cond: *mut cnd_t) -> libc::c_int;
+    fn cnd_timedwait(
+        __cond: *mut cnd_t,
+        __mutex: *mut mtx_t,
+        __time_point: *const timespec,
+    ) -> libc::c_int;
+    fn cnd_destroy(__COND: *mut cnd_t);
+    fn timespec_get(__ts: *mut timespec, __base: libc::c_int) -> libc::c_int;
+}
+pub type __time_t = libc::c_long;
+pub type __syscall_slong_t = libc::c_long;
+pub type time_t = __time_t;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct timespec {
+    pub tv_sec: __time_t,
+    pub tv_nsec: __syscall_slong_t,
+}
+pub type thrd_t = libc::c_ulong;
+pub type thrd_start_t = Option::<unsafe extern "C" fn(*mut libc::c_void) -> libc::c_int>;
+pub type __anonenum_33 = libc::c_uint;
+pub const thrd_timedout: __anonenum_33 = 4;
+pub const thrd_nomem: __anonenum_33 = 3;
+pub const thrd_error: __anonenum_33 = 2;
+pub const thrd_busy: __anonenum_33 = 1;
+pub const thrd_success: __anonenum_33 = 0;
+pub type __anonenum_34 = libc::c_uint;
+pub const mtx_timed: __anonenum_34 = 2;
+pub const mtx_recursive: __anonenum_34 = 1;
+pub const mtx_plain: __anonenum_34 = 0;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_mtx_t_1019 {
+    pub __size: [libc::c_char; 40],
+    pub __align: libc::c_long,
+}
+pub type mtx_t = __anonunion_mtx_t_1019;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_cnd_t_1020 {
+    pub __size: [libc::c_char; 48],
+    pub __align: libc::c_longlong,
+}
+pub type cnd_t = __anonunion_cnd_t_1020;
+pub static mut ready: libc::c_int = 0 as libc::c_int;
+pub static mut m: mtx_t = __anonunion_mtx_t_1019 {
+    __size: [0; 40],
+};
+pub static mut c: cnd_t = __anonunion_cnd_t_1020 {
+    __size: [0; 48],
+};
+pub unsafe extern "C" fn t_fun(mut arg: *mut libc::c_void) -> libc::c_int {
+    mtx_lock(&mut m);
+    ready = 1 as libc::c_int;
+    cnd_signal(&mut c);
+    mtx_unlock(&mut m);
+    return 0 as libc::c_int;
+}
+unsafe fn main_0() -> libc::c_int {
+    let mut id: thrd_t = 0;
+    let mut ts: timespec = timespec { tv_sec: 0, tv_nsec: 0 };
+    let mut rc: libc::c_int = 0;
+    rc = 0 as libc::c_int;
+    mtx_init(&mut m, mtx_plain as libc::c_int);
+    cnd_init(&mut c);
+    thrd_create(
+        &mut id as *mut thrd_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> libc::c_int),
+        0 as *mut libc::c_void,
+    );
+    timespec_get(&mut ts, 1 as libc::c_int);
+    ts.tv_sec += 1 as libc::c_long;
+    mtx_lock(&mut m);
+    while ready == 0 as libc::c_int {
+        rc = cnd_timedwait(
+            &mut c as *mut cnd_t,
+            &mut m as *mut mtx_t,
+            &mut ts as *mut timespec as *const timespec,
+        );
+        if rc == thrd_timedout as libc::c_int {
+            break;
+        }
+    }
+    mtx_unlock(&mut m);
+    thrd_join(id, 0 as *mut libc::c_void as *mut libc::c_int);
+    mtx_destroy(&mut m);
+    cnd_destroy(&mut c);
+    return 0 as libc::c_int;
+}
+pub fn main() {
+    unsafe { ::std::process::exit(main_0() as i32) }
+}
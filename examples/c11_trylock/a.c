@@ -0,0 +1,36 @@
+#include <threads.h>
+
+int n = 0;
+mtx_t m;
+
+void f1() {
+    if (mtx_trylock(&m) == thrd_success) {
+        n = n + 1;
+        mtx_unlock(&m);
+    } else {
+        mtx_lock(&m);
+        n = n + 1;
+        mtx_unlock(&m);
+    }
+
+    if (mtx_trylock(&m) != thrd_success) {
+        mtx_lock(&m);
+    }
+    n = n + 1;
+    mtx_unlock(&m);
+}
+
+int t_fun(void *arg) {
+    f1();
+    return 0;
+}
+
+int main() {
+    thrd_t id1, id2;
+    mtx_init(&m, mtx_plain);
+    thrd_create(&id1, t_fun, NULL);
+    thrd_create(&id2, t_fun, NULL);
+    thrd_join(id1, NULL);
+    thrd_join(id2, NULL);
+    mtx_destroy(&m);
+}
@@ -0,0 +1,82 @@
+use ::libc;
+extern "C" {
+    fn thrd_create(
+        __thr: *mut thrd_t,
+        __func: thrd_start_t,
+        __arg: *mut libc::c_void,
+    ) -> libc::c_int;
+    fn thrd_join(__thr: thrd_t, __res: *mut libc::c_int) -> libc::c_int;
+    fn mtx_init(__mutex: *mut mtx_t, __type: libc::c_int) -> libc::c_int;
+    fn mtx_lock(__mutex: *mut mtx_t) -> libc::c_int;
+    fn mtx_trylock(__mutex: *mut mtx_t) -> libc::c_int;
+    fn mtx_unlock(__mutex: *mut mtx_t) -> libc::c_int;
+    fn mtx_destroy(__mutex: *mut mtx_t);
+}
+pub type thrd_t = libc::c_ulong;
+pub type thrd_start_t = Option::<unsafe extern "C" fn(*mut libc::c_void) -> libc::c_int>;
+pub type __anonenum_33 = libc::c_uint;
+pub const thrd_timedout: __anonenum_33 = 4;
+pub const thrd_nomem: __anonenum_33 = 3;
+pub const thrd_error: __anonenum_33 = 2;
+pub const thrd_busy: __anonenum_33 = 1;
+pub const thrd_success: __anonenum_33 = 0;
+pub type __anonenum_34 = libc::c_uint;
+pub const mtx_timed: __anonenum_34 = 2;
+pub const mtx_recursive: __anonenum_34 = 1;
+pub const mtx_plain: __anonenum_34 = 0;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_mtx_t_1019 {
+    pub __size: [libc::c_char; 40],
+    pub __align: libc::c_long,
+}
+pub type mtx_t = __anonunion_mtx_t_1019;
+pub static mut n: libc::c_int = 0 as libc::c_int;
+pub static mut m: mtx_t = __anonunion_mtx_t_1019 {
+    __size: [0; 40],
+};
+pub unsafe extern "C" fn f1() {
+    let mut tmp: libc::c_int = 0;
+    let mut tmp___0: libc::c_int = 0;
+    tmp = mtx_trylock(&mut m);
+    if tmp == thrd_success as libc::c_int {
+        n += 1;
+        mtx_unlock(&mut m);
+    } else {
+        mtx_lock(&mut m);
+        n += 1;
+        mtx_unlock(&mut m);
+    }
+    tmp___0 = mtx_trylock(&mut m);
+    if tmp___0 != thrd_success as libc::c_int {
+        mtx_lock(&mut m);
+    }
+    n += 1;
+    mtx_unlock(&mut m);
+}
+pub unsafe extern "C" fn t_fun(mut arg: *mut libc::c_void) -> libc::c_int {
+    f1();
+    return 0 as libc::c_int;
+}
+unsafe fn main_0() -> libc::c_int {
+    let mut id1: thrd_t = 0;
+    let mut id2: thrd_t = 0;
+    mtx_init(&mut m, mtx_plain as libc::c_int);
+    thrd_create(
+        &mut id1 as *mut thrd_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> libc::c_int),
+        0 as *mut libc::c_void,
+    );
+    thrd_create(
+        &mut id2 as *mut thrd_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> libc::c_int),
+        0 as *mut libc::c_void,
+    );
+    thrd_join(id1, 0 as *mut libc::c_void as *mut libc::c_int);
+    thrd_join(id2, 0 as *mut libc::c_void as *mut libc::c_int);
+    mtx_destroy(&mut m);
+    return 0 as libc::c_int;
+}
+pub fn main() {
+    unsafe { ::std::process::exit(main_0() as i32) }
+}
@@ -0,0 +1,25 @@
+#include <pthread.h>
+
+int hits = 0;
+int flag = 0;
+int misses = 0;
+
+void *t_fun(void *arg) {
+    __sync_fetch_and_add(&hits, 1);
+    __sync_bool_compare_and_swap(&flag, 0, 1);
+    // a plain write in a thread is not an initialization; not retyped
+    __sync_fetch_and_add(&misses, 1);
+    misses = 0;
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+    // initialization before the threads start
+    hits = 0;
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+    return __sync_fetch_and_add(&hits, 0) != 2;
+}
@@ -0,0 +1,56 @@
+use ::libc;
+extern "C" {
+    fn pthread_create(
+        __newthread: *mut pthread_t,
+        __attr: *const pthread_attr_t,
+        __start_routine: Option::<
+            unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void,
+        >,
+        __arg: *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_join(
+        __th: pthread_t,
+        __thread_return: *mut *mut libc::c_void,
+    ) -> libc::c_int;
+}
+pub type pthread_t = libc::c_ulong;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union pthread_attr_t {
+    pub __size: [libc::c_char; 56],
+    pub __align: libc::c_long,
+}
+pub static mut hits: libc::c_int = 0 as libc::c_int;
+pub static mut flag: libc::c_int = 0 as libc::c_int;
+pub static mut misses: libc::c_int = 0 as libc::c_int;
+pub unsafe extern "C" fn t_fun(mut arg: *mut libc::c_void) -> *mut libc::c_void {
+    ::std::intrinsics::atomic_xadd_seqcst(&mut hits, 1 as libc::c_int);
+    (::std::intrinsics::atomic_cxchg(&mut flag, 0 as libc::c_int, 1 as libc::c_int)).1;
+    ::std::intrinsics::atomic_xadd_seqcst(&mut misses, 1 as libc::c_int);
+    misses = 0 as libc::c_int;
+    return 0 as *mut libc::c_void;
+}
+unsafe fn main_0() -> libc::c_int {
+    let mut id1: pthread_t = 0;
+    let mut id2: pthread_t = 0;
+    hits = 0 as libc::c_int;
+    pthread_create(
+        &mut id1 as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        0 as *mut libc::c_void,
+    );
+    pthread_create(
+        &mut id2 as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        0 as *mut libc::c_void,
+    );
+    pthread_join(id1, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    pthread_join(id2, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    return (::std::intrinsics::atomic_xadd_seqcst(&mut hits, 0 as libc::c_int)
+        != 2 as libc::c_int) as libc::c_int;
+}
+pub fn main() {
+    unsafe { ::std::process::exit(main_0() as i32) }
+}
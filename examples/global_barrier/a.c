@@ -0,0 +1,28 @@
+#include <pthread.h>
+
+int serial = 0;
+pthread_mutex_t m = PTHREAD_MUTEX_INITIALIZER;
+pthread_barrier_t b;
+
+void *t_fun(void *arg) {
+    // one of the threads is told it is the serial thread
+    int r = pthread_barrier_wait(&b);
+    if (r == PTHREAD_BARRIER_SERIAL_THREAD) {
+        pthread_mutex_lock(&m);
+        serial = serial + 1;
+        pthread_mutex_unlock(&m);
+    }
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+
+    pthread_barrier_init(&b, NULL, 3);
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_barrier_wait(&b);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+    pthread_barrier_destroy(&b);
+}
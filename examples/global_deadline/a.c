@@ -0,0 +1,52 @@
+#include <pthread.h>
+#include <time.h>
+
+struct job {
+    int id;
+    struct timespec deadline;
+};
+
+int n = 0;
+
+pthread_mutex_t m = PTHREAD_MUTEX_INITIALIZER;
+pthread_cond_t cond = PTHREAD_COND_INITIALIZER;
+
+void wait_until(const struct timespec *deadline) {
+    pthread_mutex_lock(&m);
+    n = n + 1;
+    if (n == 1) {
+        pthread_cond_timedwait(&cond, &m, deadline);
+    } else {
+        pthread_cond_signal(&cond);
+    }
+    pthread_mutex_unlock(&m);
+}
+
+void wait_job(struct job *j) {
+    pthread_mutex_lock(&m);
+    n = n + 1;
+    if (n == 3) {
+        pthread_cond_timedwait(&cond, &m, &j->deadline);
+    } else {
+        pthread_cond_signal(&cond);
+    }
+    pthread_mutex_unlock(&m);
+}
+
+void *t_fun(void *arg) {
+    struct job j;
+    j.id = 0;
+    clock_gettime(CLOCK_REALTIME, &j.deadline);
+    j.deadline.tv_sec += (time_t) 1;
+    wait_until(&j.deadline);
+    wait_job(&j);
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+}
@@ -0,0 +1,222 @@
+use ::libc;
+extern "C" {
+    fn pthread_create(
+        __newthread: *mut pthread_t,
+        __attr: *const pthread_attr_t,
+        __start_routine: Option::<
+            unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void,
+        >,
+        __arg: *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_join(
+        __th: pthread_t,
+        __thread_return: *mut *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_mutex_lock(__mutex: *mut pthread_mutex_t) -> libc::c_int;
+    fn pthread_mutex_unlock(__mutex: *mut pthread_mutex_t) -> libc::c_int;
+    fn pthread_cond_signal(__cond: *mut pthread_cond_t) -> libc::c_int;
+    fn pthread_cond_timedwait(
+        __cond: *mut pthread_cond_t,
+        __mutex: *mut pthread_mutex_t,
+        __abstime: *const timespec,
+    ) -> libc::c_int;
+    fn clock_gettime(__clock_id: clockid_t, __tp: *mut timespec) -> libc::c_int;
+}
+pub type __time_t = libc::c_long;
+pub type __clockid_t = libc::c_int;
+pub type __syscall_slong_t = libc::c_long;
+pub type clockid_t = __clockid_t;
+pub type time_t = __time_t;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct timespec {
+    pub tv_sec: __time_t,
+    pub tv_nsec: __syscall_slong_t,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct job {
+    pub id: libc::c_int,
+    pub deadline: timespec,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __pthread_internal_list {
+    pub __prev: *mut __pthread_internal_list,
+    pub __next: *mut __pthread_internal_list,
+}
+pub type __pthread_list_t = __pthread_internal_list;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __pthread_mutex_s {
+    pub __lock: libc::c_int,
+    pub __count: libc::c_uint,
+    pub __owner: libc::c_int,
+    pub __nusers: libc::c_uint,
+    pub __kind: libc::c_int,
+    pub __spins: libc::c_short,
+    pub __elision: libc::c_short,
+    pub __list: __pthread_list_t,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __anonstruct___wseq32_112954846 {
+    pub __low: libc::c_uint,
+    pub __high: libc::c_uint,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion____missing_field_name_456658959 {
+    pub __wseq: libc::c_ulonglong,
+    pub __wseq32: __anonstruct___wseq32_112954846,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __anonstruct___g1_start32_554396209 {
+    pub __low: libc::c_uint,
+    pub __high: libc::c_uint,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion____missing_field_name_554396208 {
+    pub __g1_start: libc::c_ulonglong,
+    pub __g1_start32: __anonstruct___g1_start32_554396209,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __pthread_cond_s {
+    pub __annonCompField1: __anonunion____missing_field_name_456658959,
+    pub __annonCompField2: __anonunion____missing_field_name_554396208,
+    pub __g_refs: [libc::c_uint; 2],
+    pub __g_size: [libc::c_uint; 2],
+    pub __g1_orig_size: libc::c_uint,
+    pub __wrefs: libc::c_uint,
+    pub __g_signals: [libc::c_uint; 2],
+}
+pub type pthread_t = libc::c_ulong;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union pthread_attr_t {
+    pub __size: [libc::c_char; 56],
+    pub __align: libc::c_long,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_pthread_mutex_t_335460617 {
+    pub __data: __pthread_mutex_s,
+    pub __size: [libc::c_char; 40],
+    pub __align: libc::c_long,
+}
+pub type pthread_mutex_t = __anonunion_pthread_mutex_t_335460617;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_pthread_cond_t_951761805 {
+    pub __data: __pthread_cond_s,
+    pub __size: [libc::c_char; 48],
+    pub __align: libc::c_longlong,
+}
+pub type pthread_cond_t = __anonunion_pthread_cond_t_951761805;
+pub static mut n: libc::c_int = 0 as libc::c_int;
+pub static mut m: pthread_mutex_t = __anonunion_pthread_mutex_t_335460617 {
+    __data: {
+        let mut init = __pthread_mutex_s {
+            __lock: 0 as libc::c_int,
+            __count: 0 as libc::c_uint,
+            __owner: 0 as libc::c_int,
+            __nusers: 0 as libc::c_uint,
+            __kind: 0 as libc::c_int,
+            __spins: 0 as libc::c_int as libc::c_short,
+            __elision: 0 as libc::c_int as libc::c_short,
+            __list: {
+                let mut init = __pthread_internal_list {
+                    __prev: 0 as *const __pthread_internal_list
+                        as *mut __pthread_internal_list,
+                    __next: 0 as *const __pthread_internal_list
+                        as *mut __pthread_internal_list,
+                };
+                init
+            },
+        };
+        init
+    },
+};
+pub static mut cond: pthread_cond_t = __anonunion_pthread_cond_t_951761805 {
+    __data: {
+        let mut init = __pthread_cond_s {
+            __annonCompField1: __anonunion____missing_field_name_456658959 {
+                __wseq: 0 as libc::c_ulonglong,
+            },
+            __annonCompField2: __anonunion____missing_field_name_554396208 {
+                __g1_start: 0 as libc::c_ulonglong,
+            },
+            __g_refs: [0 as libc::c_uint, 0 as libc::c_uint],
+            __g_size: [0 as libc::c_uint, 0 as libc::c_uint],
+            __g1_orig_size: 0 as libc::c_uint,
+            __wrefs: 0 as libc::c_uint,
+            __g_signals: [0 as libc::c_uint, 0 as libc::c_uint],
+        };
+        init
+    },
+};
+pub unsafe extern "C" fn wait_until(mut deadline: *const timespec) {
+    pthread_mutex_lock(&mut m);
+    n += 1;
+    if n == 1 as libc::c_int {
+        pthread_cond_timedwait(
+            &mut cond as *mut pthread_cond_t,
+            &mut m as *mut pthread_mutex_t,
+            deadline,
+        );
+    } else {
+        pthread_cond_signal(&mut cond);
+    }
+    pthread_mutex_unlock(&mut m);
+}
+pub unsafe extern "C" fn wait_job(mut j: *mut job) {
+    pthread_mutex_lock(&mut m);
+    n += 1;
+    if n == 3 as libc::c_int {
+        pthread_cond_timedwait(
+            &mut cond as *mut pthread_cond_t,
+            &mut m as *mut pthread_mutex_t,
+            &mut (*j).deadline as *mut timespec as *const timespec,
+        );
+    } else {
+        pthread_cond_signal(&mut cond);
+    }
+    pthread_mutex_unlock(&mut m);
+}
+pub unsafe extern "C" fn t_fun(mut arg: *mut libc::c_void) -> *mut libc::c_void {
+    let mut j: job = job {
+        id: 0,
+        deadline: timespec { tv_sec: 0, tv_nsec: 0 },
+    };
+    j.id = 0 as libc::c_int;
+    clock_gettime(0 as libc::c_int, &mut j.deadline);
+    j.deadline.tv_sec += 1 as libc::c_long;
+    wait_until(&mut j.deadline as *mut timespec as *const timespec);
+    wait_job(&mut j);
+    return 0 as *mut libc::c_void;
+}
+unsafe fn main_0() -> libc::c_int {
+    let mut id1: pthread_t = 0;
+    let mut id2: pthread_t = 0;
+    pthread_create(
+        &mut id1 as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        0 as *mut libc::c_void,
+    );
+    pthread_create(
+        &mut id2 as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        0 as *mut libc::c_void,
+    );
+    pthread_join(id1, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    pthread_join(id2, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    return 0 as libc::c_int;
+}
+pub fn main() {
+    unsafe { ::std::process::exit(main_0() as i32) }
+}
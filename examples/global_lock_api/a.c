@@ -0,0 +1,36 @@
+#include <pthread.h>
+
+void my_lock(const char *who, pthread_mutex_t *m);
+void my_unlock(const char *who, pthread_mutex_t *m);
+int my_trylock(int spins, pthread_mutex_t *m);
+void my_wait(pthread_mutex_t *m, pthread_cond_t *c);
+
+int n = 0;
+int ready = 0;
+pthread_mutex_t m = PTHREAD_MUTEX_INITIALIZER;
+pthread_cond_t c = PTHREAD_COND_INITIALIZER;
+
+void *t_fun(void *arg) {
+    my_lock("t_fun", &m);
+    if (ready == 0) {
+        my_wait(&m, &c);
+    }
+    n = n + 1;
+    my_unlock("t_fun", &m);
+
+    if (my_trylock(10, &m) == 0) {
+        n = n + 1;
+        my_unlock("t_fun", &m);
+    }
+    return NULL;
+}
+
+int main() {
+    pthread_t id;
+    pthread_create(&id, NULL, t_fun, NULL);
+    my_lock("main", &m);
+    ready = 1;
+    pthread_cond_signal(&c);
+    my_unlock("main", &m);
+    pthread_join(id, NULL);
+}
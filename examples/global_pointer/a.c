@@ -0,0 +1,27 @@
+#include <pthread.h>
+#include <stdlib.h>
+
+int v = 0;
+int *buf;
+int *r;
+
+pthread_mutex_t m = PTHREAD_MUTEX_INITIALIZER;
+
+void *t_fun(void *arg) {
+    pthread_mutex_lock(&m);
+    buf[0] = buf[0] + 1;
+    r = &v;
+    pthread_mutex_unlock(&m);
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+    buf = malloc(sizeof(int) * 4);
+    buf[0] = 0;
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+    return 0;
+}
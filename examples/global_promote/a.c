@@ -0,0 +1,30 @@
+#include <pthread.h>
+
+int cnt = 0;
+int max = 0;
+
+pthread_mutex_t cnt_mutex = PTHREAD_MUTEX_INITIALIZER;
+pthread_mutex_t max_mutex = PTHREAD_MUTEX_INITIALIZER;
+
+void *t_fun(void *arg) {
+    pthread_mutex_lock(&cnt_mutex);
+    cnt++;
+    pthread_mutex_unlock(&cnt_mutex);
+
+    // two accesses in one critical section; not promoted
+    pthread_mutex_lock(&max_mutex);
+    if (max < 10) {
+        max++;
+    }
+    pthread_mutex_unlock(&max_mutex);
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+    return cnt + max;
+}
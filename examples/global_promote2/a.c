@@ -0,0 +1,54 @@
+#include <pthread.h>
+
+int total = 0;
+int last = 0;
+
+pthread_mutex_t total_mutex = PTHREAD_MUTEX_INITIALIZER;
+pthread_mutex_t last_mutex = PTHREAD_MUTEX_INITIALIZER;
+
+void add(int *p) {
+    *p += 2;
+}
+
+void inc_total() {
+    pthread_mutex_lock(&total_mutex);
+    total++;
+    pthread_mutex_unlock(&total_mutex);
+}
+
+// borrowed; not promoted
+void add_total() {
+    pthread_mutex_lock(&total_mutex);
+    add(&total);
+    pthread_mutex_unlock(&total_mutex);
+}
+
+void inc_last() {
+    pthread_mutex_lock(&last_mutex);
+    last++;
+    pthread_mutex_unlock(&last_mutex);
+}
+
+// assigned; not promoted
+void reset_last() {
+    pthread_mutex_lock(&last_mutex);
+    last = 0;
+    pthread_mutex_unlock(&last_mutex);
+}
+
+void *t_fun(void *arg) {
+    inc_total();
+    add_total();
+    inc_last();
+    reset_last();
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+    return total + last;
+}
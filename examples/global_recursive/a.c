@@ -0,0 +1,42 @@
+#include <pthread.h>
+
+int n = 0;
+int k = 0;
+
+pthread_mutex_t m;
+pthread_mutex_t em;
+
+void inc() {
+    pthread_mutex_lock(&m);
+    n = n + 1;
+    pthread_mutex_unlock(&m);
+}
+
+void *t_fun(void *arg) {
+    pthread_mutex_lock(&m);
+    inc();
+    n = n + 1;
+    pthread_mutex_unlock(&m);
+
+    pthread_mutex_lock(&em);
+    k = k + 1;
+    if (pthread_mutex_unlock(&em) != 0) {
+        return NULL;
+    }
+    return NULL;
+}
+
+int main() {
+    pthread_mutexattr_t attr1, attr2;
+    pthread_t id1, id2;
+    pthread_mutexattr_init(&attr1);
+    pthread_mutexattr_settype(&attr1, PTHREAD_MUTEX_RECURSIVE);
+    pthread_mutex_init(&m, &attr1);
+    pthread_mutexattr_init(&attr2);
+    pthread_mutexattr_settype(&attr2, PTHREAD_MUTEX_ERRORCHECK);
+    pthread_mutex_init(&em, &attr2);
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+}
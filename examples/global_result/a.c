@@ -0,0 +1,31 @@
+#include <assert.h>
+#include <pthread.h>
+#include <stdlib.h>
+
+int n = 0;
+pthread_mutex_t m = PTHREAD_MUTEX_INITIALIZER;
+
+void *t_fun(void *arg) {
+    int rc;
+
+    if (pthread_mutex_lock(&m) != 0) {
+        abort();
+    }
+    n = n + 1;
+    assert(pthread_mutex_unlock(&m) == 0);
+
+    rc = pthread_mutex_lock(&m);
+    if (rc == 0) {
+        n = n + 1;
+        pthread_mutex_unlock(&m);
+    }
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+}
@@ -0,0 +1,35 @@
+#include <pthread.h>
+
+int n = 0;
+pthread_rwlock_t lock = PTHREAD_RWLOCK_INITIALIZER;
+
+void read_lock() {
+    pthread_rwlock_rdlock(&lock);
+}
+
+void write_lock() {
+    pthread_rwlock_wrlock(&lock);
+}
+
+int get() {
+    return n;
+}
+
+void *t_fun(void *arg) {
+    int x;
+    read_lock();
+    x = get();
+    pthread_rwlock_unlock(&lock);
+    write_lock();
+    n = x + 1;
+    pthread_rwlock_unlock(&lock);
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+}
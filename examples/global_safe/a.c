@@ -0,0 +1,32 @@
+#include <pthread.h>
+#include <string.h>
+
+int n = 0;
+int k = 0;
+
+pthread_mutex_t m = PTHREAD_MUTEX_INITIALIZER;
+pthread_mutex_t mk = PTHREAD_MUTEX_INITIALIZER;
+
+void *t_fun(void *arg) {
+    pthread_mutex_lock(&m);
+    n = n + 1;
+    pthread_mutex_unlock(&m);
+
+    pthread_mutex_lock(&mk);
+    k = k + 1;
+    pthread_mutex_unlock(&mk);
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+    int x;
+    n = 1;
+    memset(&k, 0, sizeof(k));
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+    x = n;
+    return x;
+}
@@ -0,0 +1,30 @@
+#include <pthread.h>
+
+int n = 0;
+pthread_spinlock_t l;
+
+void f1() {
+    pthread_spin_lock(&l);
+    n = n + 1;
+    pthread_spin_unlock(&l);
+
+    if (!pthread_spin_trylock(&l)) {
+        n = n + 1;
+        pthread_spin_unlock(&l);
+    }
+}
+
+void *t_fun(void *arg) {
+    f1();
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+    pthread_spin_init(&l, PTHREAD_PROCESS_PRIVATE);
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+    pthread_spin_destroy(&l);
+}
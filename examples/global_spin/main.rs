@@ -0,0 +1,72 @@
+use ::libc;
+extern "C" {
+    fn pthread_create(
+        __newthread: *mut pthread_t,
+        __attr: *const pthread_attr_t,
+        __start_routine: Option::<
+            unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void,
+        >,
+        __arg: *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_join(
+        __th: pthread_t,
+        __thread_return: *mut *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_spin_init(
+        __lock: *mut pthread_spinlock_t,
+        __pshared: libc::c_int,
+    ) -> libc::c_int;
+    fn pthread_spin_destroy(__lock: *mut pthread_spinlock_t) -> libc::c_int;
+    fn pthread_spin_lock(__lock: *mut pthread_spinlock_t) -> libc::c_int;
+    fn pthread_spin_trylock(__lock: *mut pthread_spinlock_t) -> libc::c_int;
+    fn pthread_spin_unlock(__lock: *mut pthread_spinlock_t) -> libc::c_int;
+}
+pub type pthread_t = libc::c_ulong;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union pthread_attr_t {
+    pub __size: [libc::c_char; 56],
+    pub __align: libc::c_long,
+}
+pub type pthread_spinlock_t = libc::c_int;
+pub static mut n: libc::c_int = 0 as libc::c_int;
+pub static mut l: pthread_spinlock_t = 0;
+pub unsafe extern "C" fn f1() {
+    let mut tmp: libc::c_int = 0;
+    pthread_spin_lock(&mut l);
+    n += 1;
+    pthread_spin_unlock(&mut l);
+    tmp = pthread_spin_trylock(&mut l);
+    if tmp == 0 {
+        n += 1;
+        pthread_spin_unlock(&mut l);
+    }
+}
+pub unsafe extern "C" fn t_fun(mut arg: *mut libc::c_void) -> *mut libc::c_void {
+    f1();
+    return 0 as *mut libc::c_void;
+}
+unsafe fn main_0() -> libc::c_int {
+    let mut id1: pthread_t = 0;
+    let mut id2: pthread_t = 0;
+    pthread_spin_init(&mut l, 0 as libc::c_int);
+    pthread_create(
+        &mut id1 as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        0 as *mut libc::c_void,
+    );
+    pthread_create(
+        &mut id2 as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        0 as *mut libc::c_void,
+    );
+    pthread_join(id1, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    pthread_join(id2, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    pthread_spin_destroy(&mut l);
+    return 0 as libc::c_int;
+}
+pub fn main() {
+    unsafe { ::std::process::exit(main_0() as i32) }
+}
@@ -0,0 +1,50 @@
+#include <errno.h>
+#include <pthread.h>
+#include <time.h>
+
+int x = 0;
+int y = 0;
+int ready = 0;
+pthread_mutex_t m = PTHREAD_MUTEX_INITIALIZER;
+pthread_rwlock_t l = PTHREAD_RWLOCK_INITIALIZER;
+pthread_mutex_t n = PTHREAD_MUTEX_INITIALIZER;
+pthread_cond_t c = PTHREAD_COND_INITIALIZER;
+
+void *t_fun(void *arg) {
+    struct timespec ts;
+    clock_gettime(0, &ts);
+    ts.tv_sec += 1;
+    // retried with backoff until the deadline passes
+    if (pthread_mutex_timedlock(&m, &ts) == 0) {
+        x = x + 1;
+        pthread_mutex_unlock(&m);
+    }
+    if (pthread_rwlock_timedwrlock(&l, &ts) == 0) {
+        y = y + 1;
+        pthread_rwlock_unlock(&l);
+    }
+    pthread_mutex_lock(&n);
+    ready = 1;
+    pthread_cond_signal(&c);
+    pthread_mutex_unlock(&n);
+    return NULL;
+}
+
+int main() {
+    pthread_t id;
+    struct timespec ts;
+    int rc;
+    pthread_create(&id, NULL, t_fun, NULL);
+    clock_gettime(0, &ts);
+    ts.tv_sec += 1;
+    pthread_mutex_lock(&n);
+    // becomes `wait_timeout_while`, which stops at the deadline
+    while (!ready) {
+        rc = pthread_cond_timedwait(&c, &n, &ts);
+        if (rc == ETIMEDOUT) {
+            break;
+        }
+    }
+    pthread_mutex_unlock(&n);
+    pthread_join(id, NULL);
+}
@@ -0,0 +1,296 @@
+use ::libc;
+extern "C" {
+    fn clock_gettime(__clock_id: clockid_t, __tp: *mut timespec) -> libc::c_int;
+    fn pthread_create(
+        __newthread: *mut pthread_t,
+        __attr: *const pthread_attr_t,
+        __start_routine: Option::<
+            unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void,
+        >,
+        __arg: *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_join(
+        __th: pthread_t,
+        __thread_return: *mut *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_mutex_lock(__mutex: *mut pthread_mutex_t) -> libc::c_int;
+    fn pthread_mutex_timedlock(
+        __mutex: *mut pthread_mutex_t,
+        __abstime: *const timespec,
+    ) -> libc::c_int;
+    fn pthread_mutex_unlock(__mutex: *mut pthread_mutex_t) -> libc::c_int;
+    fn pthread_rwlock_timedwrlock(
+        __rwlock: *mut pthread_rwlock_t,
+        __abstime: *const timespec,
+    ) -> libc::c_int;
+    fn pthread_rwlock_unlock(__rwlock: *mut pthread_rwlock_t) -> libc::c_int;
+    fn pthread_cond_signal(__cond: *mut pthread_cond_t) -> libc::c_int;
+    fn pthread_cond_timedwait(
+        __cond: *mut pthread_cond_t,
+        __mutex: *mut pthread_mutex_t,
+        __abstime: *const timespec,
+    ) -> libc::c_int;
+}
+pub type __time_t = libc::c_long;
+pub type __clockid_t = libc::c_int;
+pub type __syscall_slong_t = libc::c_long;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct timespec {
+    pub tv_sec: __time_t,
+    pub tv_nsec: __syscall_slong_t,
+}
+pub type clockid_t = __clockid_t;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __pthread_internal_list {
+    pub __prev: *mut __pthread_internal_list,
+    pub __next: *mut __pthread_internal_list,
+}
+pub type __pthread_list_t = __pthread_internal_list;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __pthread_mutex_s {
+    pub __lock: libc::c_int,
+    pub __count: libc::c_uint,
+    pub __owner: libc::c_int,
+    pub __nusers: libc::c_uint,
+    pub __kind: libc::c_int,
+    pub __spins: libc::c_short,
+    pub __elision: libc::c_short,
+    pub __list: __pthread_list_t,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __anonstruct___wseq32_112954846 {
+    pub __low: libc::c_uint,
+    pub __high: libc::c_uint,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion____missing_field_name_456658959 {
+    pub __wseq: libc::c_ulonglong,
+    pub __wseq32: __anonstruct___wseq32_112954846,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __anonstruct___g1_start32_554396209 {
+    pub __low: libc::c_uint,
+    pub __high: libc::c_uint,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion____missing_field_name_554396208 {
+    pub __g1_start: libc::c_ulonglong,
+    pub __g1_start32: __anonstruct___g1_start32_554396209,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __pthread_cond_s {
+    pub __annonCompField1: __anonunion____missing_field_name_456658959,
+    pub __annonCompField2: __anonunion____missing_field_name_554396208,
+    pub __g_refs: [libc::c_uint; 2],
+    pub __g_size: [libc::c_uint; 2],
+    pub __g1_orig_size: libc::c_uint,
+    pub __wrefs: libc::c_uint,
+    pub __g_signals: [libc::c_uint; 2],
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __pthread_rwlock_arch_t {
+    pub __readers: libc::c_uint,
+    pub __writers: libc::c_uint,
+    pub __wrphase_futex: libc::c_uint,
+    pub __writers_futex: libc::c_uint,
+    pub __pad3: libc::c_uint,
+    pub __pad4: libc::c_uint,
+    pub __cur_writer: libc::c_int,
+    pub __shared: libc::c_int,
+    pub __rwelision: libc::c_schar,
+    pub __pad1: [libc::c_uchar; 7],
+    pub __pad2: libc::c_ulong,
+    pub __flags: libc::c_uint,
+}
+pub type pthread_t = libc::c_ulong;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union pthread_attr_t {
+    pub __size: [libc::c_char; 56],
+    pub __align: libc::c_long,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_pthread_mutex_t_335460617 {
+    pub __data: __pthread_mutex_s,
+    pub __size: [libc::c_char; 40],
+    pub __align: libc::c_long,
+}
+pub type pthread_mutex_t = __anonunion_pthread_mutex_t_335460617;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_pthread_cond_t_951761805 {
+    pub __data: __pthread_cond_s,
+    pub __size: [libc::c_char; 48],
+    pub __align: libc::c_longlong,
+}
+pub type pthread_cond_t = __anonunion_pthread_cond_t_951761805;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_pthread_rwlock_t_656928968 {
+    pub __data: __pthread_rwlock_arch_t,
+    pub __size: [libc::c_char; 56],
+    pub __align: libc::c_long,
+}
+pub type pthread_rwlock_t = __anonunion_pthread_rwlock_t_656928968;
+pub static mut x: libc::c_int = 0 as libc::c_int;
+pub static mut y: libc::c_int = 0 as libc::c_int;
+pub static mut ready: libc::c_int = 0 as libc::c_int;
+pub static mut m: pthread_mutex_t = __anonunion_pthread_mutex_t_335460617 {
+    __data: {
+        let mut init = __pthread_mutex_s {
+            __lock: 0 as libc::c_int,
+            __count: 0 as libc::c_uint,
+            __owner: 0 as libc::c_int,
+            __nusers: 0 as libc::c_uint,
+            __kind: 0 as libc::c_int,
+            __spins: 0 as libc::c_int as libc::c_short,
+            __elision: 0 as libc::c_int as libc::c_short,
+            __list: {
+                let mut init = __pthread_internal_list {
+                    __prev: 0 as *const __pthread_internal_list
+                        as *mut __pthread_internal_list,
+                    __next: 0 as *const __pthread_internal_list
+                        as *mut __pthread_internal_list,
+                };
+                init
+            },
+        };
+        init
+    },
+};
+pub static mut l: pthread_rwlock_t = __anonunion_pthread_rwlock_t_656928968 {
+    __data: {
+        let mut init = __pthread_rwlock_arch_t {
+            __readers: 0 as libc::c_uint,
+            __writers: 0 as libc::c_uint,
+            __wrphase_futex: 0 as libc::c_uint,
+            __writers_futex: 0 as libc::c_uint,
+            __pad3: 0 as libc::c_uint,
+            __pad4: 0 as libc::c_uint,
+            __cur_writer: 0 as libc::c_int,
+            __shared: 0 as libc::c_int,
+            __rwelision: 0 as libc::c_int as libc::c_schar,
+            __pad1: [
+                0 as libc::c_int as libc::c_uchar,
+                0 as libc::c_int as libc::c_uchar,
+                0 as libc::c_int as libc::c_uchar,
+                0 as libc::c_int as libc::c_uchar,
+                0 as libc::c_int as libc::c_uchar,
+                0 as libc::c_int as libc::c_uchar,
+                0 as libc::c_int as libc::c_uchar,
+            ],
+            __pad2: 0 as libc::c_ulong,
+            __flags: 0 as libc::c_uint,
+        };
+        init
+    },
+};
+pub static mut n: pthread_mutex_t = __anonunion_pthread_mutex_t_335460617 {
+    __data: {
+        let mut init = __pthread_mutex_s {
+            __lock: 0 as libc::c_int,
+            __count: 0 as libc::c_uint,
+            __owner: 0 as libc::c_int,
+            __nusers: 0 as libc::c_uint,
+            __kind: 0 as libc::c_int,
+            __spins: 0 as libc::c_int as libc::c_short,
+            __elision: 0 as libc::c_int as libc::c_short,
+            __list: {
+                let mut init = __pthread_internal_list {
+                    __prev: 0 as *const __pthread_internal_list
+                        as *mut __pthread_internal_list,
+                    __next: 0 as *const __pthread_internal_list
+                        as *mut __pthread_internal_list,
+                };
+                init
+            },
+        };
+        init
+    },
+};
+pub static mut c: pthread_cond_t = __anonunion_pthread_cond_t_951761805 {
+    __data: {
+        let mut init = __pthread_cond_s {
+            __annonCompField1: __anonunion____missing_field_name_456658959 {
+                __wseq: 0 as libc::c_ulonglong,
+            },
+            __annonCompField2: __anonunion____missing_field_name_554396208 {
+                __g1_start: 0 as libc::c_ulonglong,
+            },
+            __g_refs: [0 as libc::c_uint, 0 as libc::c_uint],
+            __g_size: [0 as libc::c_uint, 0 as libc::c_uint],
+            __g1_orig_size: 0 as libc::c_uint,
+            __wrefs: 0 as libc::c_uint,
+            __g_signals: [0 as libc::c_uint, 0 as libc::c_uint],
+        };
+        init
+    },
+};
+pub unsafe extern "C" fn t_fun(mut arg: *mut libc::c_void) -> *mut libc::c_void {
+    let mut ts: timespec = timespec { tv_sec: 0, tv_nsec: 0 };
+    let mut tmp: libc::c_int = 0;
+    let mut tmp___0: libc::c_int = 0;
+    clock_gettime(0 as libc::c_int, &mut ts);
+    ts.tv_sec += 1;
+    tmp = pthread_mutex_timedlock(
+        &mut m as *mut pthread_mutex_t,
+        &mut ts as *mut timespec as *const timespec,
+    );
+    if tmp == 0 as libc::c_int {
+        x += 1;
+        pthread_mutex_unlock(&mut m);
+    }
+    tmp___0 = pthread_rwlock_timedwrlock(
+        &mut l as *mut pthread_rwlock_t,
+        &mut ts as *mut timespec as *const timespec,
+    );
+    if tmp___0 == 0 as libc::c_int {
+        y += 1;
+        pthread_rwlock_unlock(&mut l);
+    }
+    pthread_mutex_lock(&mut n);
+    ready = 1 as libc::c_int;
+    pthread_cond_signal(&mut c);
+    pthread_mutex_unlock(&mut n);
+    return 0 as *mut libc::c_void;
+}
+unsafe fn main_0() -> libc::c_int {
+    let mut id: pthread_t = 0;
+    let mut ts: timespec = timespec { tv_sec: 0, tv_nsec: 0 };
+    let mut rc: libc::c_int = 0;
+    pthread_create(
+        &mut id as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        0 as *mut libc::c_void,
+    );
+    clock_gettime(0 as libc::c_int, &mut ts);
+    ts.tv_sec += 1;
+    pthread_mutex_lock(&mut n);
+    while ready == 0 {
+        rc = pthread_cond_timedwait(
+            &mut c as *mut pthread_cond_t,
+            &mut n as *mut pthread_mutex_t,
+            &mut ts as *mut timespec as *const timespec,
+        );
+        if rc == 110 as libc::c_int {
+            break;
+        }
+    }
+    pthread_mutex_unlock(&mut n);
+    pthread_join(id, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    return 0 as libc::c_int;
+}
+pub fn main() {
+    unsafe { ::std::process::exit(main_0() as i32) }
+}
@@ -0,0 +1,25 @@
+#include <pthread.h>
+
+int x = 0;
+int y = 0;
+pthread_mutex_t a = PTHREAD_MUTEX_INITIALIZER;
+pthread_mutex_t b = PTHREAD_MUTEX_INITIALIZER;
+
+void *t_fun(void *arg) {
+    // when only `a` is acquired, the else branch releases it
+    if (pthread_mutex_trylock(&a) == 0 && pthread_mutex_trylock(&b) == 0) {
+        x = x + 1;
+        y = y + 1;
+        pthread_mutex_unlock(&b);
+        pthread_mutex_unlock(&a);
+    }
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+}
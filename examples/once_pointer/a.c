@@ -0,0 +1,29 @@
+#include <pthread.h>
+
+int x = 0;
+pthread_once_t once = PTHREAD_ONCE_INIT;
+pthread_once_t shared_once = PTHREAD_ONCE_INIT;
+
+void init(void) {
+    x = 1;
+}
+
+// the once is passed through a pointer, so it stays a `pthread_once_t`
+void run_once(pthread_once_t *o) {
+    pthread_once(o, init);
+}
+
+void *t_fun(void *arg) {
+    pthread_once(&once, init);
+    run_once(&shared_once);
+    return NULL;
+}
+
+int main() {
+    pthread_t id;
+
+    pthread_create(&id, NULL, t_fun, NULL);
+    pthread_once(&once, init);
+    run_once(&shared_once);
+    pthread_join(id, NULL);
+}
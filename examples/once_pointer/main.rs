@@ -0,0 +1,57 @@
+use ::libc;
+extern "C" {
+    fn pthread_create(
+        __newthread: *mut pthread_t,
+        __attr: *const pthread_attr_t,
+        __start_routine: Option::<
+            unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void,
+        >,
+        __arg: *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_join(
+        __th: pthread_t,
+        __thread_return: *mut *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_once(
+        __once_control: *mut pthread_once_t,
+        __init_routine: Option::<unsafe extern "C" fn() -> ()>,
+    ) -> libc::c_int;
+}
+pub type pthread_t = libc::c_ulong;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union pthread_attr_t {
+    pub __size: [libc::c_char; 56],
+    pub __align: libc::c_long,
+}
+pub type pthread_once_t = libc::c_int;
+pub static mut x: libc::c_int = 0 as libc::c_int;
+pub static mut once: pthread_once_t = 0 as libc::c_int;
+pub static mut shared_once: pthread_once_t = 0 as libc::c_int;
+pub unsafe extern "C" fn init() {
+    x = 1 as libc::c_int;
+}
+pub unsafe extern "C" fn run_once(mut o: *mut pthread_once_t) {
+    pthread_once(o, Some(init as unsafe extern "C" fn() -> ()));
+}
+pub unsafe extern "C" fn t_fun(mut arg: *mut libc::c_void) -> *mut libc::c_void {
+    pthread_once(&mut once, Some(init as unsafe extern "C" fn() -> ()));
+    run_once(&mut shared_once);
+    return 0 as *mut libc::c_void;
+}
+unsafe fn main_0() -> libc::c_int {
+    let mut id: pthread_t = 0;
+    pthread_create(
+        &mut id as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        0 as *mut libc::c_void,
+    );
+    pthread_once(&mut once, Some(init as unsafe extern "C" fn() -> ()));
+    run_once(&mut shared_once);
+    pthread_join(id, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    return 0 as libc::c_int;
+}
+pub fn main() {
+    unsafe { ::std::process::exit(main_0() as i32) }
+}
@@ -0,0 +1,45 @@
+#include <stdlib.h>
+#include <string.h>
+#include <pthread.h>
+
+typedef struct {
+    int n;
+    pthread_mutex_t m;
+} ss;
+
+typedef struct {
+    int n;
+    pthread_mutex_t m;
+    pthread_mutex_t ms[2];
+} tt;
+
+void *t_fun(void *arg) {
+    ss *s = arg;
+    pthread_mutex_lock(&s->m);
+    s->n = s->n + 1;
+    pthread_mutex_unlock(&s->m);
+    return NULL;
+}
+
+int main() {
+    ss *s, *d;
+    ss l;
+    tt t;
+    pthread_t id;
+
+    s = malloc(sizeof(ss));
+    // written in place, as the memory is uninitialized
+    memset(s, 0, sizeof(ss));
+    pthread_mutex_init(&s->m, NULL);
+
+    pthread_create(&id, NULL, t_fun, s);
+    pthread_join(id, NULL);
+
+    // the copies read the data under temporary guards
+    d = malloc(sizeof(ss));
+    *d = *s;
+    memcpy(&l, s, sizeof(ss));
+
+    // the array of locks cannot be zeroed; not translated
+    memset(&t, 0, sizeof(tt));
+}
@@ -0,0 +1,36 @@
+#include <pthread.h>
+
+// a project lock type, declared in `config.json`
+typedef pthread_mutex_t uv_mutex_t;
+
+typedef struct {
+    int n;
+    uv_mutex_t m;
+} ss;
+
+int g = 0;
+uv_mutex_t gm = PTHREAD_MUTEX_INITIALIZER;
+
+void *t_fun(void *arg) {
+    ss *s = arg;
+    pthread_mutex_lock(&s->m);
+    s->n = s->n + 1;
+    pthread_mutex_unlock(&s->m);
+    pthread_mutex_lock(&gm);
+    g = g + 1;
+    pthread_mutex_unlock(&gm);
+    return NULL;
+}
+
+int main() {
+    ss s;
+    pthread_t id1, id2;
+
+    s.n = 0;
+    pthread_mutex_init(&s.m, NULL);
+
+    pthread_create(&id1, NULL, t_fun, &s);
+    pthread_create(&id2, NULL, t_fun, &s);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+}
@@ -0,0 +1,43 @@
+#include <stdlib.h>
+#include <pthread.h>
+
+typedef struct {
+    int n;
+    pthread_mutex_t m;
+} ss;
+
+void ss_free(ss *s) {
+    pthread_mutex_destroy(&s->m);
+    free(s);
+}
+
+void *t_fun(void *arg) {
+    ss *s = arg;
+    pthread_mutex_lock(&s->m);
+    s->n = s->n + 1;
+    pthread_mutex_unlock(&s->m);
+    return NULL;
+}
+
+int main() {
+    ss *s;
+    ss l;
+    pthread_t id1, id2;
+
+    s = malloc(sizeof(ss));
+    s->n = 0;
+    pthread_mutex_init(&s->m, NULL);
+
+    pthread_create(&id1, NULL, t_fun, s);
+    pthread_create(&id2, NULL, t_fun, s);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+
+    // the lock is dropped in place before its memory is freed
+    ss_free(s);
+
+    // Rust drops the lock of a local
+    l.n = 0;
+    pthread_mutex_init(&l.m, NULL);
+    pthread_mutex_destroy(&l.m);
+}
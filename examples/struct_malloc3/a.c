@@ -0,0 +1,41 @@
+#include <stdlib.h>
+#include <pthread.h>
+#include <semaphore.h>
+
+typedef struct {
+    int n;
+    pthread_mutex_t m;
+    sem_t sem;
+    pthread_barrier_t b;
+} ss;
+
+// every caller passes allocated memory, so the primitives are written in place
+void ss_init(ss *s) {
+    s->n = 0;
+    pthread_mutex_init(&s->m, NULL);
+    sem_init(&s->sem, 0, 0);
+    pthread_barrier_init(&s->b, NULL, 2);
+}
+
+void *t_fun(void *arg) {
+    ss *s = arg;
+    pthread_mutex_lock(&s->m);
+    s->n = s->n + 1;
+    pthread_mutex_unlock(&s->m);
+    sem_post(&s->sem);
+    pthread_barrier_wait(&s->b);
+    return NULL;
+}
+
+int main() {
+    ss *s;
+    pthread_t id;
+
+    s = malloc(sizeof(ss));
+    ss_init(s);
+
+    pthread_create(&id, NULL, t_fun, s);
+    sem_wait(&s->sem);
+    pthread_barrier_wait(&s->b);
+    pthread_join(id, NULL);
+}
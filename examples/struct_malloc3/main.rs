@@ -0,0 +1,150 @@
+use ::libc;
+extern "C" {
+    fn malloc(_: libc::c_ulong) -> *mut libc::c_void;
+    fn pthread_create(
+        __newthread: *mut pthread_t,
+        __attr: *const pthread_attr_t,
+        __start_routine: Option::<
+            unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void,
+        >,
+        __arg: *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_join(
+        __th: pthread_t,
+        __thread_return: *mut *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_mutex_init(
+        __mutex: *mut pthread_mutex_t,
+        __mutexattr: *const pthread_mutexattr_t,
+    ) -> libc::c_int;
+    fn pthread_mutex_lock(__mutex: *mut pthread_mutex_t) -> libc::c_int;
+    fn pthread_mutex_unlock(__mutex: *mut pthread_mutex_t) -> libc::c_int;
+    fn sem_init(
+        __sem: *mut sem_t,
+        __pshared: libc::c_int,
+        __value: libc::c_uint,
+    ) -> libc::c_int;
+    fn sem_wait(__sem: *mut sem_t) -> libc::c_int;
+    fn sem_post(__sem: *mut sem_t) -> libc::c_int;
+    fn pthread_barrier_init(
+        __barrier: *mut pthread_barrier_t,
+        __attr: *const pthread_barrierattr_t,
+        __count: libc::c_uint,
+    ) -> libc::c_int;
+    fn pthread_barrier_wait(__barrier: *mut pthread_barrier_t) -> libc::c_int;
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __pthread_internal_list {
+    pub __prev: *mut __pthread_internal_list,
+    pub __next: *mut __pthread_internal_list,
+}
+pub type __pthread_list_t = __pthread_internal_list;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __pthread_mutex_s {
+    pub __lock: libc::c_int,
+    pub __count: libc::c_uint,
+    pub __owner: libc::c_int,
+    pub __nusers: libc::c_uint,
+    pub __kind: libc::c_int,
+    pub __spins: libc::c_short,
+    pub __elision: libc::c_short,
+    pub __list: __pthread_list_t,
+}
+pub type pthread_t = libc::c_ulong;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_pthread_mutexattr_t_488594144 {
+    pub __size: [libc::c_char; 4],
+    pub __align: libc::c_int,
+}
+pub type pthread_mutexattr_t = __anonunion_pthread_mutexattr_t_488594144;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union pthread_attr_t {
+    pub __size: [libc::c_char; 56],
+    pub __align: libc::c_long,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_pthread_mutex_t_335460617 {
+    pub __data: __pthread_mutex_s,
+    pub __size: [libc::c_char; 40],
+    pub __align: libc::c_long,
+}
+pub type pthread_mutex_t = __anonunion_pthread_mutex_t_335460617;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_pthread_barrier_t_951761805 {
+    pub __size: [libc::c_char; 32],
+    pub __align: libc::c_long,
+}
+pub type pthread_barrier_t = __anonunion_pthread_barrier_t_951761805;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_pthread_barrierattr_t_951761806 {
+    pub __size: [libc::c_char; 4],
+    pub __align: libc::c_int,
+}
+pub type pthread_barrierattr_t = __anonunion_pthread_barrierattr_t_951761806;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_sem_t_1020237069 {
+    pub __size: [libc::c_char; 32],
+    pub __align: libc::c_long,
+}
+pub type sem_t = __anonunion_sem_t_1020237069;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __anonstruct_ss_672045599 {
+    pub n: libc::c_int,
+    pub m: pthread_mutex_t,
+    pub sem: sem_t,
+    pub b: pthread_barrier_t,
+}
+pub type ss = __anonstruct_ss_672045599;
+pub unsafe extern "C" fn ss_init(mut s: *mut ss) {
+    (*s).n = 0 as libc::c_int;
+    pthread_mutex_init(
+        &mut (*s).m,
+        0 as *mut libc::c_void as *const pthread_mutexattr_t,
+    );
+    sem_init(&mut (*s).sem, 0 as libc::c_int, 0 as libc::c_uint);
+    pthread_barrier_init(
+        &mut (*s).b,
+        0 as *mut libc::c_void as *const pthread_barrierattr_t,
+        2 as libc::c_uint,
+    );
+}
+pub unsafe extern "C" fn t_fun(mut arg: *mut libc::c_void) -> *mut libc::c_void {
+    let mut s: *mut ss = 0 as *mut ss;
+    s = arg as *mut ss;
+    pthread_mutex_lock(&mut (*s).m);
+    (*s).n += 1;
+    pthread_mutex_unlock(&mut (*s).m);
+    sem_post(&mut (*s).sem);
+    pthread_barrier_wait(&mut (*s).b);
+    return 0 as *mut libc::c_void;
+}
+unsafe fn main_0() -> libc::c_int {
+    let mut s: *mut ss = 0 as *mut ss;
+    let mut id: pthread_t = 0;
+    let mut tmp: *mut libc::c_void = 0 as *mut libc::c_void;
+    tmp = malloc(::std::mem::size_of::<ss>() as libc::c_ulong);
+    s = tmp as *mut ss;
+    ss_init(s);
+    pthread_create(
+        &mut id as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        s as *mut libc::c_void,
+    );
+    sem_wait(&mut (*s).sem);
+    pthread_barrier_wait(&mut (*s).b);
+    pthread_join(id, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    return 0 as libc::c_int;
+}
+pub fn main() {
+    unsafe { ::std::process::exit(main_0() as i32) }
+}
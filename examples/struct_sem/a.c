@@ -0,0 +1,41 @@
+#include <pthread.h>
+#include <semaphore.h>
+
+typedef struct {
+    int n;
+    sem_t sem;
+} ss;
+
+sem_t done;
+
+void take(sem_t *p) {
+    sem_wait(p);
+}
+
+void *t_fun(void *arg) {
+    ss *s = arg;
+    take(&s->sem);
+    s->n = s->n + 1;
+    sem_post(&s->sem);
+    sem_post(&done);
+    return NULL;
+}
+
+int main() {
+    ss s;
+    pthread_t id1, id2;
+
+    s.n = 0;
+    sem_init(&s.sem, 0, 1);
+    sem_init(&done, 0, 0);
+
+    pthread_create(&id1, NULL, t_fun, &s);
+    pthread_create(&id2, NULL, t_fun, &s);
+    sem_wait(&done);
+    sem_wait(&done);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+
+    sem_destroy(&done);
+    sem_destroy(&s.sem);
+}
@@ -0,0 +1,94 @@
+use ::libc;
+extern "C" {
+    fn pthread_create(
+        __newthread: *mut pthread_t,
+        __attr: *const pthread_attr_t,
+        __start_routine: Option::<
+            unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void,
+        >,
+        __arg: *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_join(
+        __th: pthread_t,
+        __thread_return: *mut *mut libc::c_void,
+    ) -> libc::c_int;
+    fn sem_init(
+        __sem: *mut sem_t,
+        __pshared: libc::c_int,
+        __value: libc::c_uint,
+    ) -> libc::c_int;
+    fn sem_destroy(__sem: *mut sem_t) -> libc::c_int;
+    fn sem_wait(__sem: *mut sem_t) -> libc::c_int;
+    fn sem_post(__sem: *mut sem_t) -> libc::c_int;
+}
+pub type pthread_t = libc::c_ulong;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union pthread_attr_t {
+    pub __size: [libc::c_char; 56],
+    pub __align: libc::c_long,
+}
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union __anonunion_sem_t_1020237069 {
+    pub __size: [libc::c_char; 32],
+    pub __align: libc::c_long,
+}
+pub type sem_t = __anonunion_sem_t_1020237069;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub struct __anonstruct_ss_473541021 {
+    pub n: libc::c_int,
+    pub sem: sem_t,
+}
+pub type ss = __anonstruct_ss_473541021;
+pub static mut done: sem_t = __anonunion_sem_t_1020237069 {
+    __size: [0; 32],
+};
+pub unsafe extern "C" fn take(mut p: *mut sem_t) {
+    sem_wait(p);
+}
+pub unsafe extern "C" fn t_fun(mut arg: *mut libc::c_void) -> *mut libc::c_void {
+    let mut s: *mut ss = 0 as *mut ss;
+    s = arg as *mut ss;
+    take(&mut (*s).sem);
+    (*s).n += 1;
+    sem_post(&mut (*s).sem);
+    sem_post(&mut done);
+    return 0 as *mut libc::c_void;
+}
+unsafe fn main_0() -> libc::c_int {
+    let mut s: ss = ss {
+        n: 0,
+        sem: __anonunion_sem_t_1020237069 {
+            __size: [0; 32],
+        },
+    };
+    let mut id1: pthread_t = 0;
+    let mut id2: pthread_t = 0;
+    s.n = 0 as libc::c_int;
+    sem_init(&mut s.sem, 0 as libc::c_int, 1 as libc::c_uint);
+    sem_init(&mut done, 0 as libc::c_int, 0 as libc::c_uint);
+    pthread_create(
+        &mut id1 as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        &mut s as *mut ss as *mut libc::c_void,
+    );
+    pthread_create(
+        &mut id2 as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        &mut s as *mut ss as *mut libc::c_void,
+    );
+    sem_wait(&mut done);
+    sem_wait(&mut done);
+    pthread_join(id1, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    pthread_join(id2, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    sem_destroy(&mut done);
+    sem_destroy(&mut s.sem);
+    return 0 as libc::c_int;
+}
+pub fn main() {
+    unsafe { ::std::process::exit(main_0() as i32) }
+}
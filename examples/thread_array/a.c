@@ -0,0 +1,36 @@
+#include <pthread.h>
+
+int x = 0;
+pthread_mutex_t m = PTHREAD_MUTEX_INITIALIZER;
+
+void *t_fun(void *arg) {
+    pthread_mutex_lock(&m);
+    x = x + 1;
+    pthread_mutex_unlock(&m);
+    return NULL;
+}
+
+int main() {
+    pthread_t ids[2];
+    pthread_t big, detached;
+    pthread_attr_t attr, dattr;
+
+    // elements are reached by `offset`
+    for (int i = 0; i < 2; i++) {
+        pthread_create(&ids[i], NULL, t_fun, NULL);
+    }
+    for (int i = 0; i < 2; i++) {
+        pthread_join(ids[i], NULL);
+    }
+
+    // the stack size is given to `Builder`
+    pthread_attr_init(&attr);
+    pthread_attr_setstacksize(&attr, 1 << 20);
+    pthread_create(&big, &attr, t_fun, NULL);
+    pthread_join(big, NULL);
+
+    // the detach state is not, so it stays a `pthread_t`
+    pthread_attr_init(&dattr);
+    pthread_attr_setdetachstate(&dattr, PTHREAD_CREATE_DETACHED);
+    pthread_create(&detached, &dattr, t_fun, NULL);
+}
@@ -0,0 +1,31 @@
+#include <pthread.h>
+#include <stdlib.h>
+
+pthread_key_t buf_key;
+pthread_key_t id_key;
+pthread_key_t late_key;
+pthread_once_t once = PTHREAD_ONCE_INIT;
+
+void init_id_key(void) {
+    pthread_key_create(&id_key, NULL);
+}
+
+void *t_fun(void *arg) {
+    pthread_once(&once, init_id_key);
+    pthread_setspecific(buf_key, malloc(16));
+    pthread_setspecific(id_key, arg);
+
+    // created by every thread, so it stays a `pthread_key_t`
+    pthread_key_create(&late_key, NULL);
+    pthread_setspecific(late_key, arg);
+
+    return pthread_getspecific(id_key);
+}
+
+int main() {
+    pthread_t id;
+
+    pthread_key_create(&buf_key, free);
+    pthread_create(&id, NULL, t_fun, NULL);
+    pthread_join(id, NULL);
+}
@@ -0,0 +1,76 @@
+use ::libc;
+extern "C" {
+    fn pthread_create(
+        __newthread: *mut pthread_t,
+        __attr: *const pthread_attr_t,
+        __start_routine: Option::<
+            unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void,
+        >,
+        __arg: *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_join(
+        __th: pthread_t,
+        __thread_return: *mut *mut libc::c_void,
+    ) -> libc::c_int;
+    fn pthread_once(
+        __once_control: *mut pthread_once_t,
+        __init_routine: Option::<unsafe extern "C" fn() -> ()>,
+    ) -> libc::c_int;
+    fn pthread_key_create(
+        __key: *mut pthread_key_t,
+        __destr_function: Option::<unsafe extern "C" fn(*mut libc::c_void) -> ()>,
+    ) -> libc::c_int;
+    fn pthread_getspecific(__key: pthread_key_t) -> *mut libc::c_void;
+    fn pthread_setspecific(
+        __key: pthread_key_t,
+        __pointer: *const libc::c_void,
+    ) -> libc::c_int;
+    fn malloc(_: libc::c_ulong) -> *mut libc::c_void;
+    fn free(__ptr: *mut libc::c_void);
+}
+pub type pthread_t = libc::c_ulong;
+#[derive(Copy, Clone)]
+#[repr(C)]
+pub union pthread_attr_t {
+    pub __size: [libc::c_char; 56],
+    pub __align: libc::c_long,
+}
+pub type pthread_key_t = libc::c_uint;
+pub type pthread_once_t = libc::c_int;
+pub static mut buf_key: pthread_key_t = 0;
+pub static mut id_key: pthread_key_t = 0;
+pub static mut late_key: pthread_key_t = 0;
+pub static mut once: pthread_once_t = 0 as libc::c_int;
+pub unsafe extern "C" fn init_id_key() {
+    pthread_key_create(&mut id_key, None);
+}
+pub unsafe extern "C" fn t_fun(mut arg: *mut libc::c_void) -> *mut libc::c_void {
+    let mut tmp: *mut libc::c_void = 0 as *mut libc::c_void;
+    let mut tmp___0: *mut libc::c_void = 0 as *mut libc::c_void;
+    pthread_once(&mut once, Some(init_id_key as unsafe extern "C" fn() -> ()));
+    tmp = malloc(16 as libc::c_ulong);
+    pthread_setspecific(buf_key, tmp as *const libc::c_void);
+    pthread_setspecific(id_key, arg as *const libc::c_void);
+    pthread_key_create(&mut late_key, None);
+    pthread_setspecific(late_key, arg as *const libc::c_void);
+    tmp___0 = pthread_getspecific(id_key);
+    return tmp___0;
+}
+unsafe fn main_0() -> libc::c_int {
+    let mut id: pthread_t = 0;
+    pthread_key_create(
+        &mut buf_key,
+        Some(free as unsafe extern "C" fn(*mut libc::c_void) -> ()),
+    );
+    pthread_create(
+        &mut id as *mut pthread_t,
+        0 as *mut libc::c_void as *const pthread_attr_t,
+        Some(t_fun as unsafe extern "C" fn(*mut libc::c_void) -> *mut libc::c_void),
+        0 as *mut libc::c_void,
+    );
+    pthread_join(id, 0 as *mut libc::c_void as *mut *mut libc::c_void);
+    return 0 as libc::c_int;
+}
+pub fn main() {
+    unsafe { ::std::process::exit(main_0() as i32) }
+}
@@ -0,0 +1,28 @@
+#include <pthread.h>
+
+struct point {
+    int x;
+    int y;
+};
+
+__thread int counter = 0;
+__thread struct point pos;
+__thread int hist[4];
+
+void *t_fun(void *arg) {
+    int i = 1;
+    counter = counter + 1;
+    counter += 2;
+    pos.x = counter;
+    pos.y += pos.x;
+    hist[i] = pos.y;
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_join(id2, NULL);
+}
@@ -0,0 +1,29 @@
+#include <pthread.h>
+
+int x = 0;
+pthread_mutex_t m = PTHREAD_MUTEX_INITIALIZER;
+pthread_t last;
+
+void *t_fun(void *arg) {
+    pthread_mutex_lock(&m);
+    x = x + 1;
+    pthread_mutex_unlock(&m);
+    return NULL;
+}
+
+int main() {
+    pthread_t id1, id2;
+
+    // held in `JoinHandle`s
+    pthread_create(&id1, NULL, t_fun, NULL);
+    pthread_create(&id2, NULL, t_fun, NULL);
+    pthread_join(id1, NULL);
+    pthread_detach(id2);
+
+    // compared with another thread, so it stays a `pthread_t`
+    pthread_create(&last, NULL, t_fun, NULL);
+    if (pthread_equal(last, pthread_self())) {
+        return 1;
+    }
+    pthread_join(last, NULL);
+}
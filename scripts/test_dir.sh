@@ -24,6 +24,16 @@ mkdir -p $to
 rm -f $to/{*.rs,Cargo.toml,rust-toolchain,a.xml,cfg.dot,lines}
 cp -r $from/{*.rs,Cargo.toml,rust-toolchain} $to
 
+# per-example options
+ANALYSIS_ARGS=
+CONCRAT_ARGS=
+if [ -f "$from/concrat_args" ]; then
+  CONCRAT_ARGS=`cat $from/concrat_args`
+fi
+if [ -f "$from/config.json" ]; then
+  CONCRAT_ARGS="$CONCRAT_ARGS -c $from/config.json"
+fi
+
 if [ "$GOBLINT" = "yes" ]; then
   cp -r $from/{a.xml,cfg.dot,lines} $to
   CMD=goblint2json
@@ -31,15 +41,21 @@ if [ "$GOBLINT" = "yes" ]; then
 else
   CMD=dataflow
   MSG=Analyzing
+  if [ -f "$from/dataflow_args" ]; then
+    ANALYSIS_ARGS=`cat $from/dataflow_args`
+  fi
+  if [ -f "$from/config.json" ]; then
+    ANALYSIS_ARGS="$ANALYSIS_ARGS -c $from/config.json"
+  fi
 fi
 
 cp $to/main.rs $to/main_old.rs
 
 echo $MSG $from
-cargo run --release --bin $CMD -- -i $to -d deps_crate/target/debug/deps $@
+cargo run --release --bin $CMD -- -i $to -d deps_crate/target/debug/deps $ANALYSIS_ARGS $@
 
 echo Translating $from
-cargo run --release --bin concrat -- -i $to -d deps_crate/target/debug/deps $@
+cargo run --release --bin concrat -- -i $to -d deps_crate/target/debug/deps $CONCRAT_ARGS $@
 
 if [ -x "$(command -v diffstat)" ]; then
   diff -u $to/main_old.rs $to/main.rs | diffstat
@@ -47,6 +63,18 @@ fi
 
 cargo fmt -- $to/main.rs $to/main_old.rs
 
+# code expected in the translation, one snippet per line, compared without whitespace
+if [ -f "$from/expected" ]; then
+  translated=`tr -d ' \t\n' < $to/main.rs`
+  while IFS= read -r line; do
+    snippet=`echo "$line" | tr -d ' \t'`
+    if [ -n "$snippet" ] && [[ "$translated" != *"$snippet"* ]]; then
+      echo "Not found in the translation of $from: $line"
+      exit 1
+    fi
+  done < $from/expected
+fi
+
 echo Compiling $from
 nightly=`cat $to/rust-toolchain`
 RUSTFLAGS=-Awarnings cargo +$nightly build --manifest-path $to/Cargo.toml
@@ -21,6 +21,18 @@ pub struct AnalysisSummary {
     pub array_mutex_map: BTreeMap<String, String>,
     pub struct_mutex_map: BTreeMap<String, BTreeMap<String, String>>,
     pub function_map: BTreeMap<String, FunctionSummary>,
+    #[serde(default)]
+    pub mutex_kind_map: BTreeMap<String, MutexKind>,
+    #[serde(default)]
+    pub atomic_map: BTreeMap<String, String>,
+    #[serde(default)]
+    pub promoted_map: BTreeMap<String, String>,
+    /// Locks initialized in memory that may be uninitialized, e.g., allocated by `malloc`.
+    #[serde(default)]
+    pub uninit_locks: BTreeSet<String>,
+    /// Variables of each function that may hold allocated memory.
+    #[serde(default)]
+    pub alloc_map: BTreeMap<String, BTreeSet<String>>,
 }
 
 impl AnalysisSummary {
@@ -39,6 +51,54 @@ impl AnalysisSummary {
     }
 }
 
+/// Mutex types configured with `pthread_mutexattr_settype` and spinlocks kept as spinlocks;
+/// normal mutexes are omitted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MutexKind {
+    Recursive,
+    ErrorCheck,
+    Spin,
+}
+
+impl MutexKind {
+    pub fn from_type_arg(arg: &str) -> Option<Self> {
+        if arg == "1" || arg.contains("PTHREAD_MUTEX_RECURSIVE") {
+            Some(Self::Recursive)
+        } else if arg == "2" || arg.contains("PTHREAD_MUTEX_ERRORCHECK") {
+            Some(Self::ErrorCheck)
+        } else {
+            None
+        }
+    }
+
+    /// Parses the type argument of C11 `mtx_init`, where `mtx_recursive` is a flag.
+    pub fn from_c11_type_arg(arg: &str) -> Option<Self> {
+        let recursive =
+            arg.contains("mtx_recursive") || arg.parse().map_or(false, |t: i32| t & 1 != 0);
+        if recursive {
+            Some(Self::Recursive)
+        } else {
+            None
+        }
+    }
+
+    pub fn lock_type(&self) -> &'static str {
+        match self {
+            Self::Recursive => "RecursiveMutex",
+            Self::ErrorCheck => "ErrorCheckMutex",
+            Self::Spin => "SpinLock",
+        }
+    }
+
+    pub fn guard_type(&self) -> &'static str {
+        match self {
+            Self::Recursive => "RecursiveMutexGuard",
+            Self::ErrorCheck => "ErrorCheckMutexGuard",
+            Self::Spin => "SpinLockGuard",
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Eq, Default, Serialize, Deserialize)]
 pub struct FunctionSummary {
     pub entry_mutex: Vec<ExprPath>,
@@ -49,6 +109,14 @@ pub struct FunctionSummary {
     pub mutex_line: BTreeMap<ExprPath, BTreeSet<usize>>,
     pub rdlock_line: BTreeMap<ExprPath, BTreeSet<usize>>,
     pub wrlock_line: BTreeMap<ExprPath, BTreeSet<usize>>,
+    /// Depths at which recursive and errorcheck mutexes are held before the lock and unlock
+    /// calls on each line.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub mutex_depth: BTreeMap<ExprPath, BTreeMap<usize, usize>>,
+    /// Lines where locks may be held, as they may be released later, if known; not compared in
+    /// the tests of the analysis.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub may_line: Option<BTreeMap<ExprPath, BTreeSet<usize>>>,
 }
 
 impl FunctionSummary {
@@ -61,6 +129,8 @@ impl FunctionSummary {
         mutex_line: BTreeMap<ExprPath, BTreeSet<usize>>,
         rdlock_line: BTreeMap<ExprPath, BTreeSet<usize>>,
         wrlock_line: BTreeMap<ExprPath, BTreeSet<usize>>,
+        mutex_depth: BTreeMap<ExprPath, BTreeMap<usize, usize>>,
+        may_line: Option<BTreeMap<ExprPath, BTreeSet<usize>>>,
     ) -> Self {
         entry_mutex.sort();
         entry_mutex.dedup();
@@ -81,8 +151,25 @@ impl FunctionSummary {
             mutex_line,
             rdlock_line,
             wrlock_line,
+            mutex_depth,
+            may_line,
         }
     }
+
+    /// Locks held on entry, in the order of the guard parameters.
+    pub fn entry_locks(&self) -> Vec<ExprPath> {
+        let mut locks = self.entry_mutex.clone();
+        locks.extend(self.entry_rwlock.iter().cloned());
+        locks
+    }
+
+    /// Locks held on return, in the order of the returned guards.
+    pub fn ret_locks(&self) -> Vec<ExprPath> {
+        let mut locks = self.ret_mutex.clone();
+        locks.extend(self.ret_rdlock.iter().cloned());
+        locks.extend(self.ret_wrlock.iter().cloned());
+        locks
+    }
 }
 
 pub fn summarize(
@@ -121,6 +208,11 @@ pub fn summarize(
         array_mutex_map,
         struct_mutex_map,
         function_map,
+        mutex_kind_map: BTreeMap::new(),
+        atomic_map: BTreeMap::new(),
+        promoted_map: BTreeMap::new(),
+        uninit_locks: BTreeSet::new(),
+        alloc_map: BTreeMap::new(),
     }
 }
 
@@ -409,6 +501,8 @@ fn generate_function_map(
                         mutex_line,
                         BTreeMap::new(),
                         BTreeMap::new(),
+                        BTreeMap::new(),
+                        None,
                     ),
                 )
             },
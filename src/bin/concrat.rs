@@ -50,6 +50,20 @@ fn main() {
                 .required(true)
                 .takes_value(true),
         )
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .short("c")
+                .help("lock API configuration file")
+                .takes_value(true),
+        )
+        .arg(
+            Arg::with_name("safe-globals")
+                .long("safe-globals")
+                .short("g")
+                .help("emit lock, condition variable and once globals as immutable statics")
+                .takes_value(false),
+        )
         .arg(
             Arg::with_name("dry-run")
                 .long("dry-run")
@@ -65,6 +79,17 @@ fn main() {
         .get_matches();
     let mut input = PathBuf::from(matches.value_of("input").unwrap());
     let dep = PathBuf::from(matches.value_of("dependency").unwrap());
+
+    if let Some(config) = matches.value_of("config") {
+        match config::Config::from_json_file(&PathBuf::from(config)) {
+            Ok(config) => config::init(config),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+    let safe_globals = matches.is_present("safe-globals");
     let dry_run = matches.is_present("dry-run");
     let verbose = matches.is_present("verbose");
 
@@ -80,7 +105,7 @@ fn main() {
     let args = util::compile_args(&input, &dep);
     input.pop();
 
-    let replacements = rewrite::collect_replacements(args, summary);
+    let replacements = rewrite::collect_replacements(args, summary, safe_globals);
 
     if verbose {
         for replacement in &replacements {
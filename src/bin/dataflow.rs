@@ -57,6 +57,27 @@ fn main() {
                 .help("verbose")
                 .takes_value(false),
         )
+        .arg(
+            Arg::with_name("atomic")
+                .long("atomic")
+                .short("a")
+                .help("promote lock-protected counters to atomics")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("spin")
+                .long("spin")
+                .short("s")
+                .help("keep spinlocks as spin-based locks")
+                .takes_value(false),
+        )
+        .arg(
+            Arg::with_name("config")
+                .long("config")
+                .short("c")
+                .help("lock API configuration file")
+                .takes_value(true),
+        )
         .arg(
             Arg::with_name("test")
                 .long("test")
@@ -66,15 +87,27 @@ fn main() {
         )
         .get_matches();
     let verbose = matches.is_present("verbose");
+    let atomic = matches.is_present("atomic");
+    let spin = matches.is_present("spin");
     let test = matches.is_present("test");
     let mut input = PathBuf::from(matches.value_of("input").unwrap());
     let dep = PathBuf::from(matches.value_of("dependency").unwrap());
 
+    if let Some(config) = matches.value_of("config") {
+        match config::Config::from_json_file(&PathBuf::from(config)) {
+            Ok(config) => config::init(config),
+            Err(e) => {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        }
+    }
+
     input.push("c2rust-lib.rs");
     let args = util::compile_args(&input, &dep);
     input.pop();
 
-    let summary = dataflow::run(args, verbose);
+    let mut summary = dataflow::run(args, verbose, atomic, spin);
 
     if test {
         input.push("b.json");
@@ -82,6 +115,10 @@ fn main() {
         input.pop();
 
         let summary2: analysis::AnalysisSummary = serde_json::from_reader(file).unwrap();
+        // may-held lines are only used by the rewriter and are not in the expected summaries
+        for s in summary.function_map.values_mut() {
+            s.may_line = None;
+        }
         assert_eq!(summary.mutex_map, summary2.mutex_map, "mutex_map");
         assert_eq!(
             summary.array_mutex_map, summary2.array_mutex_map,
@@ -0,0 +1,187 @@
+use std::{collections::BTreeMap, fs::File, path::Path};
+
+use serde::{Deserialize, Serialize};
+use spin::once::Once;
+
+use crate::analysis::MutexKind;
+
+static CONFIG: Once<Config> = Once::new();
+
+/// Registers the user configuration; must be called before the analysis or the rewriting.
+pub fn init(config: Config) {
+    CONFIG.call_once(|| config);
+}
+
+/// The user-declared lock API named `f`, if any.
+pub fn lock_api(f: &str) -> Option<&'static LockApi> {
+    CONFIG.get()?.functions.get(f)
+}
+
+/// The pthread type of the user-declared lock type `ty`, if any.
+pub fn lock_type(ty: &str) -> Option<&'static str> {
+    CONFIG.get()?.types.get(ty).map(LockKind::pthread_type)
+}
+
+/// The names of the user-declared lock types.
+pub fn lock_type_names() -> impl Iterator<Item = &'static String> {
+    CONFIG
+        .get()
+        .into_iter()
+        .flat_map(|config| config.types.keys())
+}
+
+/// Lock APIs declared by the user, e.g., project wrappers around pthreads whose bodies are not
+/// available, and the lock types they take, e.g., typedefs of pthread types.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct Config {
+    #[serde(default)]
+    pub functions: BTreeMap<String, LockApi>,
+    #[serde(default)]
+    pub types: BTreeMap<String, LockKind>,
+}
+
+impl Config {
+    pub fn from_json_file(path: &Path) -> Result<Self, String> {
+        let file =
+            File::open(path).map_err(|e| format!("cannot open {}: {}", path.display(), e))?;
+        serde_json::from_reader(file)
+            .map_err(|e| format!("cannot read the configuration {}: {}", path.display(), e))
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct LockApi {
+    pub op: LockOp,
+    #[serde(default)]
+    pub kind: LockKind,
+    /// The index of the lock argument; defaults to the position in the pthread counterpart.
+    #[serde(default)]
+    pub arg: Option<usize>,
+    /// The index of the condition variable argument of `wait`.
+    #[serde(default)]
+    pub cond: Option<usize>,
+    /// The index of the attribute argument of `init`.
+    #[serde(default)]
+    pub attr: Option<usize>,
+    #[serde(default)]
+    pub ret: ReturnCode,
+}
+
+impl LockApi {
+    /// The pthread function performing the same operation.
+    pub fn pthread_name(&self) -> &'static str {
+        match (self.op, self.kind) {
+            (LockOp::Init, LockKind::Spin) => "pthread_spin_init",
+            (LockOp::Init, LockKind::RwLock) => "pthread_rwlock_init",
+            (LockOp::Init, _) => "pthread_mutex_init",
+            (LockOp::Destroy, LockKind::Spin) => "pthread_spin_destroy",
+            (LockOp::Destroy, LockKind::RwLock) => "pthread_rwlock_destroy",
+            (LockOp::Destroy, _) => "pthread_mutex_destroy",
+            (LockOp::Lock, LockKind::Spin) => "pthread_spin_lock",
+            (LockOp::Lock | LockOp::WrLock, LockKind::RwLock) | (LockOp::WrLock, _) => {
+                "pthread_rwlock_wrlock"
+            }
+            (LockOp::Lock, _) => "pthread_mutex_lock",
+            (LockOp::RdLock, _) => "pthread_rwlock_rdlock",
+            (LockOp::TryLock, LockKind::Spin) => "pthread_spin_trylock",
+            (LockOp::TryLock, LockKind::RwLock) => "pthread_rwlock_trywrlock",
+            (LockOp::TryLock, _) => "pthread_mutex_trylock",
+            (LockOp::Unlock, LockKind::Spin) => "pthread_spin_unlock",
+            (LockOp::Unlock, LockKind::RwLock) => "pthread_rwlock_unlock",
+            (LockOp::Unlock, _) => "pthread_mutex_unlock",
+            (LockOp::Wait, _) => "pthread_cond_wait",
+        }
+    }
+
+    /// The index of the argument at `i` of the pthread counterpart.
+    pub fn position(&self, i: usize) -> usize {
+        match (self.op, i) {
+            (LockOp::Wait, 0) => self.cond.unwrap_or(0),
+            (LockOp::Wait, 1) => self.arg.unwrap_or(1),
+            (LockOp::Init, 1) => self.attr.unwrap_or(1),
+            (_, 0) => self.arg.unwrap_or(0),
+            _ => i,
+        }
+    }
+
+    /// Reorders the arguments of a call to match the pthread counterpart.
+    pub fn reorder<T: Copy>(&self, args: &[T]) -> Vec<T> {
+        let n = if matches!(self.op, LockOp::Wait | LockOp::Init) {
+            2
+        } else {
+            1
+        };
+        let mut positions: Vec<_> = (0..n).map(|i| self.position(i)).collect();
+        positions.extend((0..args.len()).filter(|i| !positions.contains(i)));
+        positions
+            .iter()
+            .filter_map(|i| args.get(*i).copied())
+            .collect()
+    }
+
+    pub fn mutex_kind(&self) -> Option<MutexKind> {
+        match self.kind {
+            LockKind::Recursive => Some(MutexKind::Recursive),
+            LockKind::ErrorCheck => Some(MutexKind::ErrorCheck),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockOp {
+    Init,
+    Destroy,
+    Lock,
+    TryLock,
+    RdLock,
+    WrLock,
+    Unlock,
+    Wait,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LockKind {
+    Mutex,
+    Recursive,
+    ErrorCheck,
+    Spin,
+    RwLock,
+}
+
+impl LockKind {
+    /// The pthread type of locks of this kind.
+    pub fn pthread_type(&self) -> &'static str {
+        match self {
+            Self::Spin => "pthread_spinlock_t",
+            Self::RwLock => "pthread_rwlock_t",
+            _ => "pthread_mutex_t",
+        }
+    }
+}
+
+impl Default for LockKind {
+    fn default() -> Self {
+        Self::Mutex
+    }
+}
+
+/// How a lock API reports success and failure.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReturnCode {
+    /// `0` on success and an errno value on failure, as in pthreads
+    Errno,
+    /// `thrd_success` on success and `thrd_busy`, `thrd_timedout` or `thrd_error` on failure
+    C11,
+    /// no return value
+    Void,
+}
+
+impl Default for ReturnCode {
+    fn default() -> Self {
+        Self::Errno
+    }
+}
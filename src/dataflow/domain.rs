@@ -1,5 +1,6 @@
-use std::collections::BTreeSet;
+use std::collections::{BTreeMap, BTreeSet};
 
+use rustc_middle::mir::Local;
 use rustc_mir_dataflow::{fmt::DebugWithContext, lattice::JoinSemiLattice, GenKill};
 
 use crate::util::ExprPath;
@@ -9,13 +10,63 @@ pub trait Domain {
     fn unlock(&mut self, path: ExprPath);
     fn wait(&mut self, path: ExprPath);
 
+    /// Locking a recursive mutex that may already be held by the current thread.
+    fn lock_recursive(&mut self, path: ExprPath) {
+        self.lock(path);
+    }
+
+    /// Unlocking a recursive mutex, which may still be held after the unlock.
+    fn unlock_recursive(&mut self, path: ExprPath) {
+        self.unlock(path);
+    }
+
     fn lock_rd(&mut self, path: ExprPath);
     fn lock_wr(&mut self, path: ExprPath);
     fn unlock_rw(&mut self, path: ExprPath);
 
+    /// A trylock-like call whose result is stored in `local`; the lock is held only on success.
+    fn try_lock(&mut self, _local: Local, lock: TryLock) {
+        lock.acquire(self);
+    }
+
     fn custom(&mut self, entry: MayMutexSetPair, ret: MustMutexSetTriple);
 }
 
+/// A lock acquired by a trylock-like call when the call succeeds.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TryLock {
+    Mutex(ExprPath),
+    RecursiveMutex(ExprPath),
+    RdLock(ExprPath),
+    WrLock(ExprPath),
+}
+
+impl TryLock {
+    pub fn path(&self) -> &ExprPath {
+        match self {
+            Self::Mutex(p) | Self::RecursiveMutex(p) | Self::RdLock(p) | Self::WrLock(p) => p,
+        }
+    }
+
+    pub fn map<F: Fn(ExprPath) -> ExprPath>(self, f: F) -> Self {
+        match self {
+            Self::Mutex(p) => Self::Mutex(f(p)),
+            Self::RecursiveMutex(p) => Self::RecursiveMutex(f(p)),
+            Self::RdLock(p) => Self::RdLock(f(p)),
+            Self::WrLock(p) => Self::WrLock(f(p)),
+        }
+    }
+
+    pub fn acquire<D: Domain + ?Sized>(self, domain: &mut D) {
+        match self {
+            Self::Mutex(p) => domain.lock(p),
+            Self::RecursiveMutex(p) => domain.lock_recursive(p),
+            Self::RdLock(p) => domain.lock_rd(p),
+            Self::WrLock(p) => domain.lock_wr(p),
+        }
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct MayMutexSetPair {
     pub mutex: MayMutexSet,
@@ -134,6 +185,10 @@ pub struct MustMutexSetTriple {
     pub mutex: MustMutexSet,
     pub rdlock: MustMutexSet,
     pub wrlock: MustMutexSet,
+    /// Number of nested re-acquisitions of held recursive mutexes.
+    pub depth: BTreeMap<ExprPath, usize>,
+    /// Locals holding results of pending trylocks, with whether zero means success.
+    pub trylocks: BTreeMap<Local, (TryLock, bool)>,
 }
 
 impl MustMutexSetTriple {
@@ -143,6 +198,8 @@ impl MustMutexSetTriple {
             mutex: MustMutexSet::new(mutex.0),
             rdlock: MustMutexSet::new(rwlock.0.clone()),
             wrlock: MustMutexSet::new(rwlock.0),
+            depth: BTreeMap::new(),
+            trylocks: BTreeMap::new(),
         }
     }
 
@@ -151,6 +208,8 @@ impl MustMutexSetTriple {
             mutex: MustMutexSet::bottom(),
             rdlock: MustMutexSet::bottom(),
             wrlock: MustMutexSet::bottom(),
+            depth: BTreeMap::new(),
+            trylocks: BTreeMap::new(),
         }
     }
 
@@ -159,6 +218,8 @@ impl MustMutexSetTriple {
             mutex: MustMutexSet::empty(),
             rdlock: MustMutexSet::empty(),
             wrlock: MustMutexSet::empty(),
+            depth: BTreeMap::new(),
+            trylocks: BTreeMap::new(),
         }
     }
 
@@ -166,13 +227,25 @@ impl MustMutexSetTriple {
         self.mutex.retain(&f);
         self.rdlock.retain(&f);
         self.wrlock.retain(&f);
+        self.depth.retain(|p, _| f(p));
+        self.trylocks.retain(|_, (l, _)| f(l.path()));
     }
 
-    pub fn map<F: Fn(ExprPath) -> ExprPath>(self, f: F) -> Self {
+    pub fn map<F: Fn(ExprPath) -> ExprPath>(mut self, f: F) -> Self {
         Self {
             mutex: self.mutex.map(&f),
             rdlock: self.rdlock.map(&f),
             wrlock: self.wrlock.map(&f),
+            depth: self
+                .depth
+                .drain_filter(|_, _| true)
+                .map(|(p, d)| (f(p), d))
+                .collect(),
+            trylocks: self
+                .trylocks
+                .drain_filter(|_, _| true)
+                .map(|(x, (l, z))| (x, (l.map(&f), z)))
+                .collect(),
         }
     }
 
@@ -180,6 +253,10 @@ impl MustMutexSetTriple {
         self.mutex.append(that.mutex);
         self.rdlock.append(that.rdlock);
         self.wrlock.append(that.wrlock);
+        for (p, d) in that.depth {
+            let d0 = self.depth.entry(p).or_default();
+            *d0 = (*d0).max(d);
+        }
     }
 }
 
@@ -196,6 +273,24 @@ impl Domain for MustMutexSetTriple {
         self.mutex.gen(path);
     }
 
+    fn lock_recursive(&mut self, path: ExprPath) {
+        if matches!(&self.mutex, MustMutexSet::Set(s) if s.contains(&path)) {
+            *self.depth.entry(path).or_default() += 1;
+        } else {
+            self.mutex.gen(path);
+        }
+    }
+
+    fn unlock_recursive(&mut self, path: ExprPath) {
+        match self.depth.get_mut(&path) {
+            Some(d) if *d > 1 => *d -= 1,
+            Some(_) => {
+                self.depth.remove(&path);
+            }
+            None => self.mutex.kill(path),
+        }
+    }
+
     fn lock_rd(&mut self, path: ExprPath) {
         self.rdlock.gen(path);
     }
@@ -209,7 +304,12 @@ impl Domain for MustMutexSetTriple {
         self.wrlock.kill(path);
     }
 
+    fn try_lock(&mut self, local: Local, lock: TryLock) {
+        self.trylocks.insert(local, (lock, true));
+    }
+
     fn custom(&mut self, entry: MayMutexSetPair, ret: MustMutexSetTriple) {
+        self.depth.retain(|p, _| !entry.mutex.0.contains(p));
         self.mutex.kill_all(entry.mutex.0);
         self.rdlock.kill_all(entry.rwlock.0.clone());
         self.wrlock.kill_all(entry.rwlock.0);
@@ -229,10 +329,36 @@ impl Domain for MustMutexSetTriple {
 
 impl JoinSemiLattice for MustMutexSetTriple {
     fn join(&mut self, other: &Self) -> bool {
+        // a recursive mutex is held at least as deeply as on the shallower path, and a trylock
+        // result is known only if both paths agree on it
+        let b0 = match (&self.mutex, &other.mutex) {
+            (_, MustMutexSet::All) => false,
+            (MustMutexSet::All, _) => {
+                self.depth = other.depth.clone();
+                self.trylocks = other.trylocks.clone();
+                false
+            }
+            _ => {
+                let trylocks = self.trylocks.len();
+                self.trylocks
+                    .retain(|x, v| other.trylocks.get(x) == Some(v));
+                let len = self.depth.len();
+                let mut changed = false;
+                self.depth.retain(|p, d| {
+                    let d1 = *other.depth.get(p).unwrap_or(&0);
+                    if d1 < *d {
+                        *d = d1;
+                        changed = true;
+                    }
+                    *d > 0
+                });
+                changed || self.depth.len() < len || self.trylocks.len() < trylocks
+            }
+        };
         let b1 = self.mutex.join(&other.mutex);
         let b2 = self.rdlock.join(&other.rdlock);
         let b3 = self.wrlock.join(&other.wrlock);
-        b1 || b2 || b3
+        b0 || b1 || b2 || b3
     }
 }
 
@@ -271,6 +397,13 @@ impl MustMutexSet {
         }
     }
 
+    pub fn contains(&self, path: &ExprPath) -> bool {
+        match self {
+            Self::All => true,
+            Self::Set(s) => s.contains(path),
+        }
+    }
+
     pub fn retain<F: Fn(&ExprPath) -> bool>(&mut self, f: F) {
         match self {
             Self::All => (),
@@ -331,3 +464,59 @@ impl GenKill<ExprPath> for MustMutexSet {
 }
 
 impl<T> DebugWithContext<T> for MustMutexSet {}
+
+#[cfg(test)]
+mod tests {
+    use rustc_middle::mir::Local;
+    use rustc_mir_dataflow::lattice::JoinSemiLattice;
+
+    use super::{Domain, MayMutexSetPair, MustMutexSetTriple, TryLock};
+    use crate::util::ExprPath;
+
+    fn held(st: &MustMutexSetTriple, m: &ExprPath) -> bool {
+        st.mutex.clone().into_set().contains(m)
+    }
+
+    #[test]
+    fn test_recursive() {
+        let m = ExprPath::new("m".to_string(), vec![]);
+        let mut st = MustMutexSetTriple::new(MayMutexSetPair::bottom());
+        st.lock_recursive(m.clone());
+        st.lock_recursive(m.clone());
+        st.unlock_recursive(m.clone());
+        assert!(held(&st, &m));
+        st.unlock_recursive(m.clone());
+        assert!(!held(&st, &m));
+
+        let mut st1 = MustMutexSetTriple::new(MayMutexSetPair::bottom());
+        st1.lock_recursive(m.clone());
+        st1.lock_recursive(m.clone());
+        let mut st2 = MustMutexSetTriple::new(MayMutexSetPair::bottom());
+        st2.lock_recursive(m.clone());
+        st1.join(&st2);
+        assert!(held(&st1, &m));
+        st1.unlock_recursive(m.clone());
+        assert!(!held(&st1, &m));
+    }
+
+    #[test]
+    fn test_trylock() {
+        let m = ExprPath::new("m".to_string(), vec![]);
+        let x = Local::from_usize(1);
+        let mut st1 = MustMutexSetTriple::new(MayMutexSetPair::bottom());
+        st1.try_lock(x, TryLock::Mutex(m.clone()));
+        assert!(!held(&st1, &m));
+
+        let mut st2 = st1.clone();
+        st1.join(&st2);
+        assert!(st1.trylocks.contains_key(&x));
+
+        st2.trylocks.get_mut(&x).unwrap().1 = false;
+        st1.join(&st2);
+        assert!(st1.trylocks.is_empty());
+
+        let (lock, _) = st2.trylocks.remove(&x).unwrap();
+        lock.acquire(&mut st2);
+        assert!(held(&st2, &m));
+    }
+}
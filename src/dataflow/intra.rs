@@ -2,14 +2,27 @@ use std::collections::BTreeMap;
 
 use etrace::some_or;
 use rustc_lint::LateContext;
-use rustc_middle::mir::{self, BasicBlock, Body, Location, Terminator};
-use rustc_mir_dataflow::{Analysis, AnalysisDomain, Backward, CallReturnPlaces, Forward, Results};
+use rustc_middle::{
+    mir::{
+        self, BasicBlock, BinOp, Body, LocalKind, Location, Operand, Rvalue, StatementKind,
+        Terminator, TerminatorKind, UnOp,
+    },
+    ty::ParamEnv,
+};
+use rustc_mir_dataflow::{
+    Analysis, AnalysisDomain, Backward, CallReturnPlaces, Forward, Results, SwitchIntEdgeEffects,
+};
 use rustc_span::{def_id::DefId, Span};
 
 use super::{
-    domain::{Domain, MayMutexSetPair, MustMutexSetTriple},
+    domain::{Domain, MayMutexSetPair, MustMutexSetTriple, TryLock},
     get_function_call, Arg, FunctionCodeSummary, FunctionSummary,
 };
+use crate::{
+    analysis::MutexKind,
+    config::{lock_api, ReturnCode},
+    util::{lock_key, pthread_function},
+};
 
 #[allow(missing_debug_implementations)]
 #[derive(Clone)]
@@ -17,6 +30,7 @@ pub struct AnalysisContext<'a, 'tcx> {
     function_mutex_map: &'a BTreeMap<DefId, FunctionSummary>,
     functions: &'a BTreeMap<DefId, FunctionCodeSummary>,
     calls: &'a BTreeMap<Span, Vec<Arg>>,
+    mutex_kinds: &'a BTreeMap<String, MutexKind>,
     body: &'a Body<'tcx>,
     ctx: &'a LateContext<'tcx>,
 }
@@ -26,6 +40,7 @@ impl<'a, 'tcx> AnalysisContext<'a, 'tcx> {
         function_mutex_map: &'a BTreeMap<DefId, FunctionSummary>,
         functions: &'a BTreeMap<DefId, FunctionCodeSummary>,
         calls: &'a BTreeMap<Span, Vec<Arg>>,
+        mutex_kinds: &'a BTreeMap<String, MutexKind>,
         body: &'a Body<'tcx>,
         ctx: &'a LateContext<'tcx>,
     ) -> Self {
@@ -33,35 +48,90 @@ impl<'a, 'tcx> AnalysisContext<'a, 'tcx> {
             function_mutex_map,
             functions,
             calls,
+            mutex_kinds,
             body,
             ctx,
         }
     }
 
+    fn is_recursive(&self, arg: &Arg) -> bool {
+        let e = self.ctx.tcx.hir().expect_expr(arg.hir_id);
+        let key = some_or!(lock_key(self.ctx, e), return false);
+        self.mutex_kinds.get(&key) == Some(&MutexKind::Recursive)
+    }
+
+    /// Acquires `lock` only on the success branch of the result of a trylock-like call.
+    fn try_lock(
+        &self,
+        domain: &mut impl Domain,
+        terminator: &Terminator<'_>,
+        ret: ReturnCode,
+        lock: TryLock,
+    ) {
+        match &terminator.kind {
+            TerminatorKind::Call { destination, .. } if ret != ReturnCode::Void => {
+                match destination.as_local() {
+                    Some(local) => domain.try_lock(local, lock),
+                    None => lock.acquire(domain),
+                }
+            }
+            _ => lock.acquire(domain),
+        }
+    }
+
     fn terminator_effect(&self, domain: &mut impl Domain, terminator: &Terminator<'_>) {
         let f = some_or!(get_function_call(terminator), return);
         let args = some_or!(self.calls.get(&terminator.source_info.span), return);
-        let arg = |i: usize| args[i].path.clone().unwrap();
-        match self.ctx.tcx.def_path_str(f).as_str() {
-            "main::pthread_mutex_lock"
-            | "main::pthread_mutex_trylock"
-            | "main::pthread_spin_lock"
+        let name = self.ctx.tcx.def_path_str(f);
+        let api = name.strip_prefix("main::").and_then(lock_api);
+        // the index of an argument of the pthread counterpart
+        let pos = |i: usize| api.map_or(i, |api| api.position(i));
+        let arg = |i: usize| args[pos(i)].path.clone().unwrap();
+        let ret = api.map_or(ReturnCode::Errno, |api| api.ret);
+        let name = name
+            .strip_prefix("main::")
+            .and_then(pthread_function)
+            .map_or(name.clone(), |f| format!("main::{}", f));
+        match name.as_str() {
+            "main::pthread_mutex_lock" | "main::pthread_spin_lock" => {
+                if self.is_recursive(&args[pos(0)]) {
+                    domain.lock_recursive(arg(0));
+                } else {
+                    domain.lock(arg(0));
+                }
+            }
+            "main::pthread_mutex_trylock"
+            | "main::pthread_mutex_timedlock"
             | "main::pthread_spin_trylock" => {
-                domain.lock(arg(0));
+                if self.is_recursive(&args[pos(0)]) {
+                    self.try_lock(domain, terminator, ret, TryLock::RecursiveMutex(arg(0)));
+                } else {
+                    self.try_lock(domain, terminator, ret, TryLock::Mutex(arg(0)));
+                }
             }
             "main::pthread_mutex_unlock" | "main::pthread_spin_unlock" => {
-                domain.unlock(arg(0));
+                if self.is_recursive(&args[pos(0)]) {
+                    domain.unlock_recursive(arg(0));
+                } else {
+                    domain.unlock(arg(0));
+                }
             }
             "main::pthread_cond_wait" | "main::pthread_cond_timedwait" => {
                 domain.wait(arg(1));
             }
 
-            "main::pthread_rwlock_rdlock" | "main::pthread_rwlock_tryrdlock" => {
+            "main::pthread_rwlock_rdlock" => {
                 domain.lock_rd(arg(0));
             }
-            "main::pthread_rwlock_wrlock" | "main::pthread_rwlock_trywrlock" => {
+            "main::pthread_rwlock_wrlock" => {
                 domain.lock_wr(arg(0));
             }
+            "main::pthread_rwlock_tryrdlock" | "main::pthread_rwlock_timedrdlock" => {
+                self.try_lock(domain, terminator, ret, TryLock::RdLock(arg(0)));
+            }
+            "main::pthread_rwlock_trywrlock" | "main::pthread_rwlock_timedwrlock" => {
+                self.try_lock(domain, terminator, ret, TryLock::WrLock(arg(0)));
+            }
             "main::pthread_rwlock_unlock" => {
                 domain.unlock_rw(arg(0));
             }
@@ -158,7 +228,7 @@ pub struct AvailableGuards<'a, 'tcx> {
     start: MayMutexSetPair,
 }
 
-impl AnalysisDomain<'_> for AvailableGuards<'_, '_> {
+impl<'tcx> AnalysisDomain<'tcx> for AvailableGuards<'_, 'tcx> {
     type Direction = Forward;
     type Domain = MustMutexSetTriple;
 
@@ -173,13 +243,97 @@ impl AnalysisDomain<'_> for AvailableGuards<'_, '_> {
     }
 }
 
-impl Analysis<'_> for AvailableGuards<'_, '_> {
+impl<'tcx> AvailableGuards<'_, 'tcx> {
+    /// The pending trylock whose result a value carries, with whether zero means success.
+    fn trylock_of(
+        &self,
+        state: &MustMutexSetTriple,
+        rvalue: &Rvalue<'tcx>,
+    ) -> Option<(TryLock, bool)> {
+        let get = |op: &Operand<'_>| state.trylocks.get(&op.place()?.as_local()?).cloned();
+        match rvalue {
+            Rvalue::Use(op) | Rvalue::Cast(_, op, _) => get(op),
+            Rvalue::UnaryOp(UnOp::Not, op) if op.ty(self.ctx.body, self.ctx.ctx.tcx).is_bool() => {
+                get(op).map(|(l, z)| (l, !z))
+            }
+            Rvalue::BinaryOp(op @ (BinOp::Eq | BinOp::Ne), ops) => {
+                let (l, r) = &**ops;
+                let (lock, z) = if self.is_zero(r) {
+                    get(l)?
+                } else if self.is_zero(l) {
+                    get(r)?
+                } else {
+                    return None;
+                };
+                // `r == 0` is zero on failure
+                Some((lock, if *op == BinOp::Eq { !z } else { z }))
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks if `op` is zero, the success code of pthreads and C11, whose `thrd_success` is a
+    /// constant evaluated here and often cast through a temporary.
+    fn is_zero(&self, op: &Operand<'tcx>) -> bool {
+        let body = self.ctx.body;
+        let local = match op {
+            Operand::Constant(_) => return self.is_zero_constant(op),
+            Operand::Copy(p) | Operand::Move(p) => some_or!(p.as_local(), return false),
+        };
+        if body.local_kind(local) != LocalKind::Temp {
+            return false;
+        }
+        let mut rvalues = body
+            .basic_blocks()
+            .iter()
+            .flat_map(|b| &b.statements)
+            .filter_map(|s| match &s.kind {
+                StatementKind::Assign(assign) if assign.0.as_local() == Some(local) => {
+                    Some(&assign.1)
+                }
+                _ => None,
+            })
+            .peekable();
+        rvalues.peek().is_some()
+            && rvalues.all(|r| match r {
+                Rvalue::Use(op) | Rvalue::Cast(_, op, _) => self.is_zero_constant(op),
+                _ => false,
+            })
+    }
+
+    fn is_zero_constant(&self, op: &Operand<'tcx>) -> bool {
+        match op {
+            Operand::Constant(c) => c
+                .literal
+                .eval(self.ctx.ctx.tcx, ParamEnv::reveal_all())
+                .try_to_scalar_int()
+                .map_or(false, |i| i.is_null()),
+            _ => false,
+        }
+    }
+}
+
+impl<'tcx> Analysis<'tcx> for AvailableGuards<'_, 'tcx> {
     fn apply_statement_effect(
         &self,
-        _state: &mut Self::Domain,
-        _statement: &mir::Statement<'_>,
+        state: &mut Self::Domain,
+        statement: &mir::Statement<'tcx>,
         _location: Location,
     ) {
+        match &statement.kind {
+            StatementKind::Assign(assign) => {
+                let (place, rvalue) = &**assign;
+                let local = some_or!(place.as_local(), return);
+                match self.trylock_of(state, rvalue) {
+                    Some(v) => state.trylocks.insert(local, v),
+                    None => state.trylocks.remove(&local),
+                };
+            }
+            StatementKind::StorageDead(local) => {
+                state.trylocks.remove(local);
+            }
+            _ => (),
+        }
     }
 
     fn apply_terminator_effect(
@@ -188,9 +342,38 @@ impl Analysis<'_> for AvailableGuards<'_, '_> {
         terminator: &Terminator<'_>,
         _location: Location,
     ) {
+        if let TerminatorKind::Call { destination, .. } = &terminator.kind {
+            if let Some(local) = destination.as_local() {
+                state.trylocks.remove(&local);
+            }
+        }
         self.ctx.terminator_effect(state, terminator);
     }
 
+    fn apply_switch_int_edge_effects(
+        &self,
+        block: BasicBlock,
+        discr: &Operand<'_>,
+        apply_edge_effects: &mut impl SwitchIntEdgeEffects<Self::Domain>,
+    ) {
+        let local = some_or!(discr.place().and_then(|p| p.as_local()), return);
+        let zero_explicit = match &self.ctx.body[block].terminator().kind {
+            TerminatorKind::SwitchInt { targets, .. } => targets.iter().any(|(v, _)| v == 0),
+            _ => return,
+        };
+        apply_edge_effects.apply(|state, target| {
+            let (lock, zero_on_success) = some_or!(state.trylocks.remove(&local), return);
+            // the otherwise edge is nonzero only if zero has its own edge
+            let zero = match target.value {
+                Some(v) => Some(v == 0),
+                None => zero_explicit.then_some(false),
+            };
+            if zero == Some(zero_on_success) {
+                lock.acquire(state);
+            }
+        });
+    }
+
     fn apply_call_return_effect(
         &self,
         _state: &mut Self::Domain,
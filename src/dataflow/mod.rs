@@ -1,6 +1,6 @@
 use std::collections::{BTreeMap, BTreeSet};
 
-use rustc_hir::{Expr, HirId};
+use rustc_hir::{Expr, ExprKind, HirId};
 use rustc_lint::LateContext;
 use rustc_middle::mir::{Operand, Terminator, TerminatorKind};
 use rustc_mir_dataflow::JoinSemiLattice;
@@ -15,7 +15,8 @@ pub use pass::run;
 
 use self::domain::{MayMutexSetPair, MustMutexSetTriple};
 use crate::util::{
-    expr_to_path, span_to_string, type_of, type_to_string, unwrap_ptr_from_type, ExprPath,
+    expr_to_path, span_to_string, type_of, type_to_string, unwrap_cast_recursively,
+    unwrap_ptr_from_type, ExprPath,
 };
 
 #[derive(Debug, Clone)]
@@ -25,7 +26,6 @@ pub struct Arg {
     pub typ: String,
     #[allow(unused)]
     pub expr: String,
-    #[allow(unused)]
     pub hir_id: HirId,
 }
 
@@ -52,6 +52,10 @@ pub struct FunctionCodeSummary {
     accesses: Vec<(Span, ExprPath, bool)>,
     path_types: BTreeMap<ExprPath, String>,
     init_or_destroy: BTreeSet<ExprPath>,
+    atomics: Vec<(Span, ExprPath, Option<String>)>,
+    increments: BTreeSet<Span>,
+    borrows: BTreeSet<String>,
+    allocs: BTreeSet<String>,
 }
 
 impl FunctionCodeSummary {
@@ -74,6 +78,35 @@ impl FunctionCodeSummary {
     fn add_init_or_destroy(&mut self, path: ExprPath) {
         self.init_or_destroy.insert(path);
     }
+
+    fn add_atomic(&mut self, span: Span, path: ExprPath, ty: Option<String>) {
+        self.atomics.push((span, path, ty));
+    }
+
+    fn add_increment(&mut self, span: Span) {
+        self.increments.insert(span);
+    }
+
+    fn add_borrow(&mut self, x: String) {
+        self.borrows.insert(x);
+    }
+
+    fn add_alloc(&mut self, x: String) {
+        self.allocs.insert(x);
+    }
+
+    /// Checks if `e` evaluates to memory whose contents may be uninitialized: the result of an
+    /// allocation function or a variable holding one.
+    fn is_alloc(&self, ctx: &LateContext<'_>, e: &Expr<'_>) -> bool {
+        let e = unwrap_cast_recursively(e);
+        if let ExprKind::Call(f, _) = e.kind {
+            let f = span_to_string(ctx, f.span);
+            return matches!(f.as_str(), "malloc" | "calloc" | "realloc");
+        }
+        expr_to_path(ctx, e).map_or(false, |path| {
+            path.is_variable() && self.allocs.contains(&path.base)
+        })
+    }
 }
 
 #[derive(Debug)]
@@ -85,6 +118,7 @@ pub struct FunctionSummary {
     pub propagation_raw: Vec<(DefId, MustMutexSetTriple)>,
     pub access: Vec<(ExprPath, MustMutexSetTriple, bool)>,
     pub span_lock: Vec<(Span, MustMutexSetTriple)>,
+    pub span_may: Vec<(Span, MayMutexSetPair)>,
 }
 
 impl FunctionSummary {
@@ -94,6 +128,7 @@ impl FunctionSummary {
         propagation_raw: Vec<(DefId, MustMutexSetTriple)>,
         access: Vec<(ExprPath, MustMutexSetTriple, bool)>,
         span_lock: Vec<(Span, MustMutexSetTriple)>,
+        span_may: Vec<(Span, MayMutexSetPair)>,
     ) -> Self {
         let mut propagation: BTreeMap<DefId, MustMutexSetTriple> = BTreeMap::new();
         for (def_id, v) in &propagation_raw {
@@ -111,11 +146,12 @@ impl FunctionSummary {
             propagation_raw,
             access,
             span_lock,
+            span_may,
         }
     }
 
     pub fn mutex_only(entry_mutex: MayMutexSetPair, ret_mutex: MustMutexSetTriple) -> Self {
-        Self::new(entry_mutex, ret_mutex, vec![], vec![], vec![])
+        Self::new(entry_mutex, ret_mutex, vec![], vec![], vec![], vec![])
     }
 }
 
@@ -9,39 +9,53 @@ use std::{
 use etrace::some_or;
 use rustc_hir::{
     def::{DefKind, Res},
-    Expr, ExprKind, Item, ItemKind, VariantData,
+    BinOpKind, Expr, ExprKind, Item, ItemKind, Local, PatKind, VariantData,
 };
 use rustc_lint::{LateContext, LateLintPass, LintPass};
 use rustc_middle::mir::BasicBlock;
 use rustc_mir_dataflow::JoinSemiLattice;
-use rustc_span::{def_id::DefId, Span};
+use rustc_span::{def_id::DefId, source_map::Spanned, Span};
 
 use super::{
     domain::{MayMutexSetPair, MustMutexSet, MustMutexSetTriple},
     intra::{available_guards, live_guards, AnalysisContext},
-    visitor::Visitor,
+    visitor::{MayVisitor, Visitor},
     Arg, FunctionCodeSummary, FunctionSummary,
 };
 use crate::{
-    analysis::{compute_mutex_line, AnalysisSummary},
+    analysis::{compute_mutex_line, AnalysisSummary, MutexKind},
     callback::{compile_with, LatePass},
+    config::{lock_api, LockApi},
     graph::{compute_sccs, inverse, post_order, transitive_closure},
     util::{
-        current_function, def_id_to_item_name, expr_to_path, function_params, resolve_path,
-        span_lines, span_to_string, type_of, type_to_string, unwrap_call, unwrap_cast_recursively,
-        unwrap_ptr_from_type, ExprPath, ExprPathProj,
+        atomic_type, c11_function, current_function, def_id_to_item_name, expr_to_path,
+        function_params, is_atomic_intrinsic, is_thread_local, lock_key, normalize_type,
+        pthread_function, resolve_path, span_lines, span_to_string, type_of, type_to_string,
+        unwrap_call, unwrap_cast_recursively, unwrap_ptr_from_type, ExprPath, ExprPathProj,
     },
 };
 
 static VERBOSE: AtomicBool = AtomicBool::new(false);
+static PROMOTE: AtomicBool = AtomicBool::new(false);
+static SPIN: AtomicBool = AtomicBool::new(false);
 static SUMMARY: Mutex<Option<AnalysisSummary>> = Mutex::new(None);
 
 fn verbose() -> bool {
     VERBOSE.load(Ordering::Relaxed)
 }
 
-pub fn run(args: Vec<String>, verbose: bool) -> AnalysisSummary {
+fn promote() -> bool {
+    PROMOTE.load(Ordering::Relaxed)
+}
+
+fn spin() -> bool {
+    SPIN.load(Ordering::Relaxed)
+}
+
+pub fn run(args: Vec<String>, verbose: bool, promote: bool, spin: bool) -> AnalysisSummary {
     VERBOSE.store(verbose, Ordering::Relaxed);
+    PROMOTE.store(promote, Ordering::Relaxed);
+    SPIN.store(spin, Ordering::Relaxed);
     let exit_code = compile_with(args, vec![GlobalPass::new]);
     assert_eq!(exit_code, 0);
     SUMMARY.lock().unwrap().take().unwrap()
@@ -53,6 +67,14 @@ struct GlobalPass {
     mutexes_per_struct: BTreeMap<String, BTreeSet<String>>,
     thread_entries: BTreeSet<DefId>,
     globs: BTreeSet<String>,
+    mutexattr_kinds: BTreeMap<(DefId, ExprPath), MutexKind>,
+    mutex_kinds: BTreeMap<String, MutexKind>,
+    cond_mutexes: BTreeSet<ExprPath>,
+    /// mutexes locked and unlocked by calls with their keys, whose depths are reported for
+    /// attributed mutexes
+    mutex_calls: BTreeMap<Span, (ExprPath, String)>,
+    integer_globs: BTreeMap<String, String>,
+    lock_inits: Vec<(DefId, String, String)>,
 }
 
 impl GlobalPass {
@@ -84,6 +106,128 @@ impl GlobalPass {
         }
         thread_entries
     }
+
+    /// Identifies an atomic candidate by the global variable or the struct field (`T.f`).
+    fn atomic_key(&self, summary: &FunctionCodeSummary, path: &ExprPath) -> Option<String> {
+        let mut prefix = path.clone();
+        match prefix.pop() {
+            None if self.globs.contains(&path.base) => Some(path.base.clone()),
+            Some(ExprPathProj::Field(f)) => {
+                let ty = summary.path_types.get(&prefix)?;
+                Some(format!("{}.{}", ty, f))
+            }
+            _ => None,
+        }
+    }
+
+    /// Checks that a global is written only by `+=` and `-=`, so neither assigned nor borrowed,
+    /// as its atomic could not be assigned or borrowed as an integer.
+    fn only_incremented(&self, x: &str) -> bool {
+        self.functions.values().all(|summary| {
+            !summary.borrows.contains(x)
+                && summary.accesses.iter().all(|(span, path, w)| {
+                    !w || path.base != x || summary.increments.contains(span)
+                })
+        })
+    }
+
+    /// Checks that every function accesses a global at most once and never under a lock held by
+    /// its callers, so that no critical section of `lock` accesses the global twice.
+    fn accessed_once(
+        &self,
+        x: &str,
+        lock: &ExprPath,
+        function_summary_map: &BTreeMap<DefId, FunctionSummary>,
+    ) -> bool {
+        self.functions.iter().all(|(def_id, summary)| {
+            let spans: Vec<_> = summary
+                .accesses
+                .iter()
+                .filter(|(_, path, _)| path.is_variable() && path.base == x)
+                .map(|(span, _, _)| *span)
+                .collect();
+            // reads nested in writes, as in `x += 1`, belong to the same access
+            let outermost: BTreeSet<_> = spans
+                .iter()
+                .filter(|s| !spans.iter().any(|s0| s0 != *s && s0.contains(**s)))
+                .collect();
+            if outermost.is_empty() {
+                return true;
+            }
+            let held = function_summary_map.get(def_id).map_or(false, |s| {
+                let lock_set = &s.propagation_lock;
+                lock_set.mutex.contains(lock)
+                    || lock_set.rdlock.contains(lock)
+                    || lock_set.wrlock.contains(lock)
+            });
+            outermost.len() == 1 && !held
+        })
+    }
+
+    /// Finds the variables of each function that may hold allocated or `memset` memory: those
+    /// assigned such memory and the parameters receiving it from callers.
+    fn alloc_variables(&self) -> BTreeMap<DefId, BTreeSet<String>> {
+        let mut allocs: BTreeMap<_, _> = self
+            .functions
+            .iter()
+            .map(|(f, summary)| (*f, summary.allocs.clone()))
+            .collect();
+        loop {
+            let mut changed = false;
+            for (f, summary) in &self.functions {
+                for (_, callee, _, args) in &summary.calls {
+                    let params = &some_or!(self.functions.get(callee), continue).params;
+                    for (arg, (param, _)) in args.iter().zip(params) {
+                        let path = some_or!(&arg.path, continue);
+                        if path.is_variable() && allocs[f].contains(&path.base) {
+                            changed |= allocs.get_mut(callee).unwrap().insert(param.clone());
+                        }
+                    }
+                }
+            }
+            if !changed {
+                break;
+            }
+        }
+        allocs
+    }
+
+    /// Finds variables only accessed through atomic intrinsics or initialized by assignments.
+    /// An assignment is an initialization if no thread runs its function or if it writes to
+    /// memory allocated by the function, which is not yet shared.
+    fn atomic_variables(
+        &self,
+        thread_functions: &BTreeSet<DefId>,
+        allocs: &BTreeMap<DefId, BTreeSet<String>>,
+    ) -> BTreeMap<String, String> {
+        let mut atomic_map = BTreeMap::new();
+        let mut non_atomic = BTreeSet::new();
+        for (f, summary) in &self.functions {
+            for (_, path, ty) in &summary.atomics {
+                let ty = some_or!(ty, continue);
+                let key = some_or!(self.atomic_key(summary, path), continue);
+                atomic_map.insert(key, ty.clone());
+            }
+            let is_init = |p: &ExprPath| {
+                !thread_functions.contains(f)
+                    || (!p.is_variable() && allocs.get(f).map_or(false, |xs| xs.contains(&p.base)))
+            };
+            for (span, path, _) in &summary.accesses {
+                let key = some_or!(self.atomic_key(summary, path), continue);
+                if summary.atomics.iter().any(|(s, p, ty)| {
+                    s.contains(*span)
+                        && p.base == path.base
+                        && p.projections.starts_with(&path.projections)
+                        && (ty.is_some() || is_init(p))
+                }) {
+                    continue;
+                }
+                non_atomic.insert(key);
+            }
+        }
+        atomic_map.retain(|k, _| !non_atomic.contains(k));
+        atomic_map
+    }
 }
 
 impl<'tcx> LateLintPass<'tcx> for GlobalPass {
@@ -97,13 +241,24 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                 };
                 self.functions.insert(def_id, summary);
             }
-            ItemKind::Static(_, _, _) => {
+            // thread-locals are never shared between threads
+            ItemKind::Static(_, _, _) if !is_thread_local(ctx, i) => {
                 self.globs.insert(i.ident.to_string());
+                let ty = ctx.tcx.type_of(i.def_id.to_def_id());
+                if ty.is_integral() {
+                    // 128-bit integers have no atomic counterpart
+                    if let Some(aty) = atomic_type(ty) {
+                        self.integer_globs.insert(i.ident.to_string(), aty);
+                    }
+                }
             }
             ItemKind::Struct(VariantData::Struct(fs, _), _) => {
                 for f in fs.iter() {
-                    let ty = span_to_string(ctx, f.ty.span);
-                    if ty.contains("pthread_mutex_t") || ty.contains("pthread_spinlock_t") {
+                    let ty = normalize_type(span_to_string(ctx, f.ty.span));
+                    if ty.contains("pthread_mutex_t")
+                        || ty.contains("pthread_spinlock_t")
+                        || ty.contains("pthread_rwlock_t")
+                    {
                         self.mutexes_per_struct
                             .entry(i.ident.to_string())
                             .or_default()
@@ -115,6 +270,17 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
         }
     }
 
+    fn check_local(&mut self, ctx: &LateContext<'tcx>, l: &'tcx Local<'tcx>) {
+        let curr = some_or!(current_function(ctx), return);
+        let summary = some_or!(self.functions.get_mut(&curr), return);
+        let init = some_or!(l.init, return);
+        if let PatKind::Binding(_, _, x, _) = l.pat.kind {
+            if summary.is_alloc(ctx, init) {
+                summary.add_alloc(x.to_string());
+            }
+        }
+    }
+
     fn check_expr(&mut self, ctx: &LateContext<'tcx>, e: &'tcx Expr<'tcx>) {
         let curr = some_or!(current_function(ctx), return);
         let summary = some_or!(self.functions.get_mut(&curr), return);
@@ -125,45 +291,153 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                 summary.add_access(e.span, path, false);
             }
         }
+        let is_compound = matches!(e.kind, ExprKind::AssignOp(_, _, _));
+        let is_increment = matches!(
+            e.kind,
+            ExprKind::AssignOp(
+                Spanned {
+                    node: BinOpKind::Add | BinOpKind::Sub,
+                    ..
+                },
+                _,
+                _
+            )
+        );
         match &e.kind {
             ExprKind::Call(f, arg_exprs) => {
                 let args: Vec<_> = arg_exprs.iter().map(|arg| Arg::new(ctx, arg)).collect();
 
+                let f_name = span_to_string(ctx, f.span);
+                let api = lock_api(&f_name);
+                // the index of an argument of the pthread counterpart
+                let pos = |i: usize| api.map_or(i, |api| api.position(i));
+
                 let mut add_mutex = |i: usize| {
-                    let mutex = args[i].path.clone().unwrap();
+                    let mutex = args[pos(i)].path.clone().unwrap();
                     summary.add_mutex(mutex);
                 };
 
-                let f_name = span_to_string(ctx, f.span);
-                match f_name.as_str() {
+                if let Some(kind) = api.and_then(LockApi::mutex_kind) {
+                    if let Some(key) = lock_key(ctx, &arg_exprs[pos(0)]) {
+                        self.mutex_kinds.insert(key, kind);
+                    }
+                }
+
+                let pthread_name = pthread_function(&f_name).unwrap_or(&f_name);
+                if spin() && pthread_name.starts_with("pthread_spin_") {
+                    if let Some(key) = lock_key(ctx, &arg_exprs[pos(0)]) {
+                        self.mutex_kinds.insert(key, MutexKind::Spin);
+                    }
+                }
+
+                let c11 = c11_function(&f_name);
+                match pthread_name {
                     "pthread_mutex_lock"
                     | "pthread_mutex_unlock"
                     | "pthread_mutex_trylock"
+                    | "pthread_mutex_timedlock"
                     | "pthread_spin_lock"
                     | "pthread_spin_unlock"
-                    | "pthread_spin_trylock" => {
+                    | "pthread_spin_trylock"
+                    | "pthread_rwlock_rdlock"
+                    | "pthread_rwlock_wrlock"
+                    | "pthread_rwlock_tryrdlock"
+                    | "pthread_rwlock_trywrlock"
+                    | "pthread_rwlock_timedrdlock"
+                    | "pthread_rwlock_timedwrlock"
+                    | "pthread_rwlock_unlock" => {
                         add_mutex(0);
+                        if matches!(pthread_name, "pthread_mutex_lock" | "pthread_mutex_unlock") {
+                            let mutex = args[pos(0)].path.clone();
+                            let key = lock_key(ctx, &arg_exprs[pos(0)]);
+                            if let (Some(mutex), Some(key)) = (mutex, key) {
+                                self.mutex_calls.insert(e.span, (mutex, key));
+                            }
+                        }
+                    }
+                    "pthread_cond_init" | "sem_init" | "pthread_barrier_init" => {
+                        let path = args[pos(0)].path.as_ref();
+                        if let (Some(path), Some(key)) = (path, lock_key(ctx, &arg_exprs[pos(0)])) {
+                            self.lock_inits.push((curr, path.base.clone(), key));
+                        }
                     }
                     "pthread_cond_wait" | "pthread_cond_timedwait" => {
                         add_mutex(1);
+                        if let Some(mutex) = args[pos(1)].path.clone() {
+                            self.cond_mutexes.insert(mutex);
+                        }
                     }
                     "pthread_create" => {
-                        let t_fun = unwrap_cast_recursively(unwrap_call(&arg_exprs[2]));
+                        let i = if c11.is_some() { 1 } else { 2 };
+                        let t_fun = unwrap_cast_recursively(unwrap_call(&arg_exprs[i]));
                         if let Some(Res::Def(DefKind::Fn, t_fun_id)) = resolve_path(ctx, t_fun) {
                             self.thread_entries.insert(t_fun_id);
                         }
                     }
+                    "pthread_once" => {
+                        // the callback runs synchronously in the caller
+                        let init = unwrap_cast_recursively(unwrap_call(&arg_exprs[1]));
+                        if let Some(Res::Def(DefKind::Fn, init_id)) = resolve_path(ctx, init) {
+                            let init_name = span_to_string(ctx, init.span);
+                            summary.add_call(init.span, init_id, init_name, vec![]);
+                        }
+                    }
+                    "pthread_mutexattr_settype" => {
+                        let kind = MutexKind::from_type_arg(&span_to_string(
+                            ctx,
+                            unwrap_cast_recursively(&arg_exprs[1]).span,
+                        ));
+                        if let (Some(attr), Some(kind)) = (args[0].path.clone(), kind) {
+                            self.mutexattr_kinds.insert((curr, attr), kind);
+                        }
+                    }
                     "pthread_mutex_init"
                     | "pthread_mutex_destroy"
                     | "pthread_spin_init"
-                    | "pthread_spin_destroy" => {
+                    | "pthread_spin_destroy"
+                    | "pthread_rwlock_init"
+                    | "pthread_rwlock_destroy" => {
+                        if pthread_name == "pthread_mutex_init" && c11.is_none() {
+                            let kind = args
+                                .get(pos(1))
+                                .and_then(|attr| attr.path.as_ref())
+                                .and_then(|attr| self.mutexattr_kinds.get(&(curr, attr.clone())));
+                            let key = lock_key(ctx, &arg_exprs[pos(0)]);
+                            if let (Some(key), Some(kind)) = (key, kind) {
+                                self.mutex_kinds.insert(key, *kind);
+                            }
+                        } else if f_name == "mtx_init" {
+                            let kind = MutexKind::from_c11_type_arg(&span_to_string(
+                                ctx,
+                                unwrap_cast_recursively(&arg_exprs[1]).span,
+                            ));
+                            if let (Some(key), Some(kind)) = (lock_key(ctx, &arg_exprs[0]), kind) {
+                                self.mutex_kinds.insert(key, kind);
+                            }
+                        }
                         add_mutex(0);
-                        if let Some(mut path) = args[0].path.clone() {
+                        if let Some(mut path) = args[pos(0)].path.clone() {
+                            if pthread_name.ends_with("_init") {
+                                if let Some(key) = lock_key(ctx, &arg_exprs[pos(0)]) {
+                                    self.lock_inits.push((curr, path.base.clone(), key));
+                                }
+                            }
                             if path.pop().is_some() {
                                 summary.add_init_or_destroy(path);
                             }
                         }
                     }
+                    "memset" => {
+                        if let Some(path) = args[0].path.clone() {
+                            summary.add_alloc(path.base);
+                        }
+                    }
+                    _ if is_atomic_intrinsic(&f_name) && !arg_exprs.is_empty() => {
+                        let ty = unwrap_ptr_from_type(type_of(ctx, arg_exprs[0].hir_id));
+                        if let (Some(path), Some(ty)) = (args[0].path.clone(), atomic_type(ty)) {
+                            summary.add_atomic(arg_exprs[0].span, path, Some(ty));
+                        }
+                    }
                     _ => (),
                 }
 
@@ -171,8 +445,24 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                     summary.add_call(e.span, def_id, f_name, args);
                 }
             }
-            ExprKind::Assign(e, _, _) | ExprKind::AssignOp(_, e, _) => {
+            ExprKind::AddrOf(_, _, e) => {
+                let path = some_or!(expr_to_path(ctx, e), return);
+                if path.is_variable() && self.globs.contains(&path.base) {
+                    summary.add_borrow(path.base);
+                }
+            }
+            ExprKind::Assign(e, rhs, _) | ExprKind::AssignOp(_, e, rhs) => {
                 let mut path = some_or!(expr_to_path(ctx, e), return);
+                if !is_compound && path.is_variable() && summary.is_alloc(ctx, rhs) {
+                    summary.add_alloc(path.base.clone());
+                }
+                // plain assignments may initialize atomic variables
+                if !is_compound {
+                    summary.add_atomic(e.span, path.clone(), None);
+                }
+                if is_increment {
+                    summary.add_increment(e.span);
+                }
                 while !path.is_variable() || self.globs.contains(&path.base) {
                     summary.add_access(e.span, path.clone(), true);
                     if path.pop().is_none() {
@@ -227,11 +517,13 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
             let mut ret_mutexes = vec![];
             let mut propagations = vec![];
             let mut span_mutexes = vec![];
+            let mut span_mays = vec![];
             for _ in &funcs {
                 entry_mutexes.push(MayMutexSetPair::bottom());
                 ret_mutexes.push(MustMutexSetTriple::bottom());
                 propagations.push(vec![]);
                 span_mutexes.push(vec![]);
+                span_mays.push(vec![]);
             }
 
             let span_args_map = self
@@ -262,12 +554,17 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                         &function_summary_map,
                         &self.functions,
                         &span_args_map,
+                        &self.mutex_kinds,
                         body,
                         ctx,
                     );
 
                     // live guard analysis
-                    let mut results = live_guards(ana_ctx.clone()).into_results_cursor(body);
+                    let results = live_guards(ana_ctx.clone());
+                    let mut may_visitor = MayVisitor::default();
+                    results.visit_reachable_with(body, &mut may_visitor);
+                    span_mays[i] = may_visitor.span_may;
+                    let mut results = results.into_results_cursor(body);
                     results.seek_to_block_start(BasicBlock::from_usize(0));
                     let entry_mutex = results.get().clone();
 
@@ -301,12 +598,14 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                 }
             }
 
-            for ((((def_id, entry_mutex), ret_mutex), mut propagation), span_mutex) in funcs
-                .drain(..)
-                .zip(entry_mutexes.drain(..))
-                .zip(ret_mutexes.drain(..))
-                .zip(propagations.drain(..))
-                .zip(span_mutexes.drain(..))
+            for (((((def_id, entry_mutex), ret_mutex), mut propagation), span_mutex), span_may) in
+                funcs
+                    .drain(..)
+                    .zip(entry_mutexes.drain(..))
+                    .zip(ret_mutexes.drain(..))
+                    .zip(propagations.drain(..))
+                    .zip(span_mutexes.drain(..))
+                    .zip(span_mays.drain(..))
             {
                 // guards propagated by function calls
                 let propagation = propagation
@@ -337,7 +636,14 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                 // create summary
                 function_summary_map.insert(
                     def_id,
-                    FunctionSummary::new(entry_mutex, ret_mutex, propagation, access, span_mutex),
+                    FunctionSummary::new(
+                        entry_mutex,
+                        ret_mutex,
+                        propagation,
+                        access,
+                        span_mutex,
+                        span_may,
+                    ),
                 );
             }
         }
@@ -436,6 +742,7 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                 mutex,
                 rdlock,
                 wrlock,
+                ..
             } = &mut abs_st;
             let summary = function_summary_map.get_mut(&def_id).unwrap();
             let MayMutexSetPair {
@@ -448,6 +755,8 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
             summary.propagation_lock = abs_st;
         }
 
+        // paths accessed while each lock is held
+        let mut lock_accesses: BTreeMap<ExprPath, BTreeSet<ExprPath>> = BTreeMap::new();
         // accesses to global variables
         let mut global_access: BTreeMap<_, Vec<_>> = BTreeMap::new();
         // accesses to struct fields
@@ -463,6 +772,7 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                     mut mutex,
                     rdlock,
                     wrlock,
+                    ..
                 } = v;
                 mutex.append(wrlock);
                 let mut ms: Vec<_> = mutex
@@ -477,6 +787,12 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                         .map(|m| (m, false))
                         .collect(),
                 );
+                for (m, _) in &ms {
+                    lock_accesses
+                        .entry(m.clone())
+                        .or_default()
+                        .insert(path.clone());
+                }
                 if path.is_struct() {
                     struct_access.push((path.clone(), *def_id, ms, *w));
                 } else {
@@ -494,12 +810,24 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
             println!("thread_functions: {:#?}", thread_functions);
         }
 
+        // find variables that can become atomic
+        let allocs = self.alloc_variables();
+        let mut atomic_map = self.atomic_variables(&thread_functions, &allocs);
+        if verbose() {
+            println!("atomic_map: {:#?}", atomic_map);
+        }
+
         let mut mutex_map: BTreeMap<String, String> = BTreeMap::new();
         let mut array_mutex_map: BTreeMap<String, String> = BTreeMap::new();
         let mut struct_mutex_map: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
 
         // for each global variable access path
         for (path, mut accesses) in global_access {
+            // skip atomic
+            if path.is_variable() && atomic_map.contains_key(&path.base) {
+                continue;
+            }
+
             // skip read-only
             if accesses.iter().all(|(_, _, w)| !w) {
                 continue;
@@ -529,10 +857,10 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                 .max_by_key(|(_, x)| *x);
             let (cand, _) = some_or!(cand_opt, continue);
 
-            // split accesses into safe/unsafe accesses
+            // split accesses into safe/unsafe accesses; writes need the write lock of rwlocks
             let (safe, usafe): (Vec<_>, _) = accesses
                 .drain(..)
-                .partition(|(_, ms, w)| ms.iter().any(|(m, w0)| m == &cand && !w || *w0));
+                .partition(|(_, ms, w)| ms.iter().any(|(m, w0)| m == &cand && (!w || *w0)));
 
             // skip read-only
             if safe.iter().all(|(_, _, w)| !w) {
@@ -604,6 +932,11 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
 
         // for each struct field access path
         for ((typ, field), mut accesses) in struct_access_per_type {
+            // skip atomic
+            if atomic_map.contains_key(&format!("{}.{}", typ, field)) {
+                continue;
+            }
+
             // skip read-only
             if accesses.iter().all(|(_, _, _, w)| !w) {
                 continue;
@@ -642,10 +975,10 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
             let empty = BTreeSet::new();
             let init_or_destroy = some_or!(init_or_destroy_map.get(&typ), &empty);
 
-            // split accesses into safe/unsafe accesses
+            // split accesses into safe/unsafe accesses; writes need the write lock of rwlocks
             let (safe, usafe): (Vec<_>, _) = accesses
                 .drain(..)
-                .partition(|(_, _, ms, w)| ms.iter().any(|(m, w0)| m == &cand && !w || *w0));
+                .partition(|(_, _, ms, w)| ms.iter().any(|(m, w0)| m == &cand && (!w || *w0)));
 
             // skip read-only
             if safe.iter().all(|(_, _, _, w)| !w) {
@@ -682,7 +1015,43 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
             }
         }
 
+        // promote integers that are the only data of their locks to atomics
+        let mut promoted_map = BTreeMap::new();
+        if promote() {
+            for (x, m) in &mutex_map {
+                let ty = some_or!(self.integer_globs.get(x), continue);
+                let lock = ExprPath::new(m.clone(), vec![]);
+                // the lock must protect nothing else, including struct fields and data of
+                // functions receiving its guard
+                let protects_other = lock_accesses.get(&lock).map_or(false, |paths| {
+                    paths
+                        .iter()
+                        .any(|p| p.base != *m && (!p.is_variable() || p.base != *x))
+                });
+                let guard_param = function_summary_map.values().any(|s| {
+                    s.entry_lock.mutex.0.contains(&lock) || s.entry_lock.rwlock.0.contains(&lock)
+                });
+                if mutex_map.values().filter(|m0| *m0 == m).count() > 1
+                    || array_mutex_map.values().any(|m0| m0 == m)
+                    || protects_other
+                    || guard_param
+                    || self.cond_mutexes.contains(&lock)
+                    || !self.only_incremented(x)
+                    || !self.accessed_once(x, &lock, &function_summary_map)
+                {
+                    continue;
+                }
+                promoted_map.insert(x.clone(), m.clone());
+                atomic_map.insert(x.clone(), ty.clone());
+            }
+            mutex_map.retain(|x, _| !promoted_map.contains_key(x));
+        }
+        // promoted locks are deleted
+        let is_kept =
+            |m: &ExprPath| !m.is_variable() || !promoted_map.values().any(|x| x == &m.base);
+
         if verbose() {
+            println!("{:?}", promoted_map);
             println!("{:?}", mutex_map);
             println!("{:?}", array_mutex_map);
             println!("{:?}", struct_mutex_map);
@@ -718,28 +1087,54 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                     ret_lock,
                     propagation_lock,
                     span_lock,
+                    span_may,
                     ..
                 } = summary;
                 let mut entry_mutex = entry_lock.clone().mutex.into_vec();
+                entry_mutex.retain(is_kept);
                 let mut entry_rwlock = entry_lock.clone().rwlock.into_vec();
+                entry_rwlock.retain(is_kept);
                 let mut ret_mutex = ret_lock.clone().mutex.into_vec();
+                ret_mutex.retain(is_kept);
                 let mut ret_rdlock = ret_lock.clone().rdlock.into_vec();
+                ret_rdlock.retain(is_kept);
                 let mut ret_wrlock = ret_lock.clone().wrlock.into_vec();
-                let prop_mutex = propagation_lock.clone().mutex.into_vec();
+                ret_wrlock.retain(is_kept);
+                let mut prop_mutex = propagation_lock.clone().mutex.into_vec();
+                prop_mutex.retain(is_kept);
                 for m in &prop_mutex {
                     entry_mutex.push(m.clone());
                     ret_mutex.push(m.clone());
                 }
-                let prop_rdlock = propagation_lock.clone().rdlock.into_vec();
+                let mut prop_rdlock = propagation_lock.clone().rdlock.into_vec();
+                prop_rdlock.retain(is_kept);
                 for m in &prop_rdlock {
                     entry_rwlock.push(m.clone());
                     ret_rdlock.push(m.clone());
                 }
-                let prop_wrlock = propagation_lock.clone().wrlock.into_vec();
+                let mut prop_wrlock = propagation_lock.clone().wrlock.into_vec();
+                prop_wrlock.retain(is_kept);
                 for m in &prop_wrlock {
                     entry_rwlock.push(m.clone());
                     ret_wrlock.push(m.clone());
                 }
+                let mut mutex_depth: BTreeMap<ExprPath, BTreeMap<usize, usize>> = BTreeMap::new();
+                for (span, v) in span_lock {
+                    let (m, key) = some_or!(self.mutex_calls.get(span), continue);
+                    let attributed = matches!(
+                        self.mutex_kinds.get(key),
+                        Some(MutexKind::Recursive | MutexKind::ErrorCheck)
+                    );
+                    let held = prop_mutex.contains(m)
+                        || matches!(&v.mutex, MustMutexSet::Set(s) if s.contains(m));
+                    if attributed && held && is_kept(m) {
+                        let depth = 1 + v.depth.get(m).copied().unwrap_or(0);
+                        let lines = mutex_depth.entry(m.clone()).or_default();
+                        for l in span_lines(ctx, *span) {
+                            lines.insert(l, depth);
+                        }
+                    }
+                }
                 let mut span_mutex_map: BTreeMap<_, Vec<_>> = BTreeMap::new();
                 let mut span_rdlock_map: BTreeMap<_, Vec<_>> = BTreeMap::new();
                 let mut span_wrlock_map: BTreeMap<_, Vec<_>> = BTreeMap::new();
@@ -758,6 +1153,7 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                 }
                 let compute_line = |mut span_map: BTreeMap<Span, Vec<ExprPath>>| {
                     for v in span_map.values_mut() {
+                        v.retain(is_kept);
                         v.sort();
                         v.dedup();
                     }
@@ -766,6 +1162,13 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                 let mutex_line = compute_line(span_mutex_map);
                 let rdlock_line = compute_line(span_rdlock_map);
                 let wrlock_line = compute_line(span_wrlock_map);
+                let mut may_line: BTreeMap<ExprPath, BTreeSet<usize>> = BTreeMap::new();
+                for (span, v) in span_may {
+                    for m in v.mutex.0.iter().chain(&v.rwlock.0).filter(|&m| is_kept(m)) {
+                        let lines = may_line.entry(m.clone()).or_default();
+                        lines.extend(span_lines(ctx, *span));
+                    }
+                }
                 let f = def_id_to_item_name(ctx.tcx, *def_id);
                 let summary = crate::analysis::FunctionSummary::new(
                     entry_mutex,
@@ -776,15 +1179,35 @@ impl<'tcx> LateLintPass<'tcx> for GlobalPass {
                     mutex_line,
                     rdlock_line,
                     wrlock_line,
+                    mutex_depth,
+                    Some(may_line),
                 );
                 (f, summary)
             })
             .collect();
+        // locks initialized in memory that may be uninitialized
+        let uninit_locks = self
+            .lock_inits
+            .iter()
+            .filter(|(f, x, _)| allocs.get(f).map_or(false, |xs| xs.contains(x)))
+            .map(|(_, _, key)| key.clone())
+            .collect();
+        let alloc_map = allocs
+            .into_iter()
+            .filter(|(_, xs)| !xs.is_empty())
+            .map(|(f, xs)| (def_id_to_item_name(ctx.tcx, f), xs))
+            .collect();
+
         let summary = AnalysisSummary {
             mutex_map,
             array_mutex_map,
             struct_mutex_map,
             function_map,
+            mutex_kind_map: self.mutex_kinds.clone(),
+            atomic_map,
+            promoted_map,
+            uninit_locks,
+            alloc_map,
         };
         *SUMMARY.lock().unwrap() = Some(summary);
     }
@@ -3,7 +3,7 @@ use rustc_middle::mir::{Location, Statement, Terminator, TerminatorKind};
 use rustc_mir_dataflow::ResultsVisitor;
 use rustc_span::{def_id::DefId, Span};
 
-use super::domain::MustMutexSetTriple;
+use super::domain::{MayMutexSetPair, MustMutexSetTriple};
 use crate::dataflow::get_function_call;
 
 #[derive(Default, Debug)]
@@ -31,7 +31,9 @@ impl<'mir, 'tcx> ResultsVisitor<'mir, 'tcx> for Visitor {
         }
 
         if matches!(&terminator.kind, TerminatorKind::Return) {
-            self.return_state = Some(state.clone());
+            let mut state = state.clone();
+            state.trylocks.clear();
+            self.return_state = Some(state);
         }
 
         let func = some_or!(get_function_call(terminator), return);
@@ -48,3 +50,36 @@ impl<'mir, 'tcx> ResultsVisitor<'mir, 'tcx> for Visitor {
         self.span_mutex.push((span, state.clone()));
     }
 }
+
+/// Records the locks that may be held, as guards live in the backward analysis, after each call
+/// and statement.
+#[derive(Default, Debug)]
+pub struct MayVisitor {
+    pub span_may: Vec<(Span, MayMutexSetPair)>,
+}
+
+impl<'mir, 'tcx> ResultsVisitor<'mir, 'tcx> for MayVisitor {
+    type FlowState = MayMutexSetPair;
+
+    fn visit_terminator_before_primary_effect(
+        &mut self,
+        state: &Self::FlowState,
+        terminator: &'mir Terminator<'tcx>,
+        _location: Location,
+    ) {
+        if matches!(&terminator.kind, TerminatorKind::Call { .. }) {
+            let span = terminator.source_info.span;
+            self.span_may.push((span, state.clone()));
+        }
+    }
+
+    fn visit_statement_before_primary_effect(
+        &mut self,
+        state: &Self::FlowState,
+        statement: &'mir Statement<'tcx>,
+        _location: Location,
+    ) {
+        let span = statement.source_info.span;
+        self.span_may.push((span, state.clone()));
+    }
+}
@@ -43,6 +43,7 @@ extern crate rustc_span;
 
 pub mod analysis;
 pub mod callback;
+pub mod config;
 pub mod dataflow;
 pub mod graph;
 pub mod parse_xml;
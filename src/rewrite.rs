@@ -2,33 +2,57 @@ use std::{
     collections::{BTreeMap, BTreeSet},
     fs::File,
     io::Read,
-    sync::Mutex,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
 };
 
 use etrace::some_or;
 use lazy_static::lazy_static;
-use rustc_hir::*;
+use rustc_hir::{
+    def::{DefKind, Res},
+    *,
+};
 use rustc_lint::{LateContext, LateLintPass, LintContext, LintPass};
 use rustc_middle::hir::nested_filter::OnlyBodies;
-use rustc_span::{BytePos, Span, Symbol};
+use rustc_span::{
+    hygiene::{ExpnId, ExpnKind, MacroKind},
+    sym, BytePos, Span, Symbol,
+};
 use rustfix::{Replacement, Snippet, Solution, Suggestion};
 use spin::once::Once;
 
+use self::{
+    atomic::{atomic_new, AtomicPass},
+    pointer::{classify_pointers, copied_pointer, pointer_source, PointerClass},
+};
 use crate::{
-    analysis::{AnalysisSummary, FunctionSummary},
+    analysis::{AnalysisSummary, FunctionSummary, MutexKind},
     callback::{compile_with, LatePass},
+    config::{lock_api, ReturnCode},
     graph::transitive_closure,
     util::{
-        expr_to_path, function_params, join, resolve_struct, span_lines, span_to_string, type_of,
-        type_to_string, unwrap_cast_recursively, unwrap_ptr_from_type, ExprPath, ExprPathProj,
+        c11_function, expr_to_path, function_params, is_thread_local, join, lock_key,
+        normalize_type, pthread_function, resolve_path, resolve_struct, span_lines, span_to_string,
+        type_of, type_to_string, unwrap_call, unwrap_cast_recursively, unwrap_ptr_from_type,
+        ExprPath, ExprPathProj,
     },
 };
 
+mod atomic;
+mod pointer;
+
 lazy_static! {
     static ref REPLACEMENTS: Mutex<Vec<Replacement>> = Mutex::new(vec![]);
 }
 
 static SUMMARY: Once<AnalysisSummary> = Once::new();
+static SAFE_GLOBALS: AtomicBool = AtomicBool::new(false);
+
+fn safe_globals() -> bool {
+    SAFE_GLOBALS.load(Ordering::Relaxed)
+}
 
 fn global_mutex_map() -> &'static BTreeMap<String, String> {
     &SUMMARY.get().unwrap().mutex_map
@@ -46,10 +70,41 @@ fn function_mutex_map() -> &'static BTreeMap<String, FunctionSummary> {
     &SUMMARY.get().unwrap().function_map
 }
 
-pub fn collect_replacements(args: Vec<String>, summary: AnalysisSummary) -> Vec<Replacement> {
+fn mutex_kind_map() -> &'static BTreeMap<String, MutexKind> {
+    &SUMMARY.get().unwrap().mutex_kind_map
+}
+
+fn atomic_map() -> &'static BTreeMap<String, String> {
+    &SUMMARY.get().unwrap().atomic_map
+}
+
+fn promoted_map() -> &'static BTreeMap<String, String> {
+    &SUMMARY.get().unwrap().promoted_map
+}
+
+fn uninit_locks() -> &'static BTreeSet<String> {
+    &SUMMARY.get().unwrap().uninit_locks
+}
+
+fn alloc_map() -> &'static BTreeMap<String, BTreeSet<String>> {
+    &SUMMARY.get().unwrap().alloc_map
+}
+
+fn lock_type_of(key: String, lock_ty: &'static str) -> &'static str {
+    mutex_kind_map()
+        .get(&key)
+        .map_or(lock_ty, MutexKind::lock_type)
+}
+
+pub fn collect_replacements(
+    args: Vec<String>,
+    summary: AnalysisSummary,
+    safe_globals: bool,
+) -> Vec<Replacement> {
     SUMMARY.call_once(|| summary);
+    SAFE_GLOBALS.store(safe_globals, Ordering::Relaxed);
 
-    let exit_code = compile_with(args, vec![RewritePass::new]);
+    let exit_code = compile_with(args, vec![RewritePass::new, AtomicPass::new]);
     assert_eq!(exit_code, 0);
 
     let mut replacements = REPLACEMENTS.lock().unwrap();
@@ -86,12 +141,32 @@ struct Visitor<'a, 'tcx> {
     mutex_init_map: BTreeSet<(String, ExprPath, String)>,
     path_type_map: BTreeMap<ExprPath, BTreeMap<String, String>>,
     duration_map: BTreeMap<(String, String, String), String>,
+    deadline_set: BTreeSet<(String, String)>,
+    condattr_clock_map: BTreeMap<(String, String), String>,
+    cond_clock_map: BTreeMap<String, String>,
     trylock_map: BTreeMap<(String, String), Vec<(usize, String)>>,
     if_map: BTreeMap<(String, String), Vec<usize>>,
     params_map: BTreeMap<String, Vec<String>>,
     ty_alias_map: BTreeMap<String, String>,
     rmutex_assign_map: BTreeMap<(String, String), String>,
     lmutex_assign_set: BTreeSet<(String, String)>,
+    thread_slots: BTreeSet<ThreadSlot>,
+    slot_misuses: Vec<(ThreadSlot, Span)>,
+    stack_size_map: BTreeMap<(String, String), String>,
+    attr_sets: BTreeSet<(String, String)>,
+    thread_attrs: Vec<(ThreadSlot, String, Option<String>, Span)>,
+    key_create_map: BTreeMap<String, Vec<(String, Option<String>, Span)>>,
+    once_callbacks: BTreeSet<String>,
+    once_misuses: Vec<(String, Span)>,
+    thread_local_map: BTreeMap<String, &'static str>,
+    c11_used: bool,
+    c11_defined: bool,
+    timed_lock_used: bool,
+    call_sites: Vec<CallSite>,
+    destroy_set: BTreeSet<(String, String)>,
+    pointer_passes: Vec<(String, String, String, usize)>,
+    pointer_assigns: Vec<(String, ExprPath, Option<PointerClass>)>,
+    unlocked_accesses: Vec<(String, ExprPath, &'tcx Expr<'tcx>)>,
 }
 
 impl<'tcx> intravisit::Visitor<'tcx> for Visitor<'_, 'tcx> {
@@ -112,22 +187,32 @@ impl<'tcx> intravisit::Visitor<'tcx> for Visitor<'_, 'tcx> {
                     .map(|f| {
                         (
                             f.ident.name.to_ident_string(),
-                            span_to_string(ctx, f.ty.span),
+                            normalize_type(span_to_string(ctx, f.ty.span)),
                         )
                     })
                     .collect();
                 self.struct_def_map.insert(name, map);
             }
             ItemKind::Static(t, _, b) => {
+                // thread-local
+                if is_thread_local(ctx, i) {
+                    let cell = if ctx.tcx.type_of(i.def_id.to_def_id()).is_scalar() {
+                        "Cell"
+                    } else {
+                        "RefCell"
+                    };
+                    self.thread_local_map.insert(name.clone(), cell);
+                }
+
                 // global
-                let ty = span_to_string(ctx, t.span);
+                let ty = normalize_type(span_to_string(ctx, t.span));
                 let init = hid_to_string(ctx, b.hir_id);
                 self.global_def_map.insert(name.clone(), (ty, init));
 
                 // array
                 match t.kind {
                     TyKind::Array(t, _) => {
-                        let ty = span_to_string(ctx, t.span);
+                        let ty = normalize_type(span_to_string(ctx, t.span));
                         let init = match &ctx.tcx.hir().body(*b).value.kind {
                             ExprKind::Block(Block { expr: Some(e), .. }, _) => &e.kind,
                             e => e,
@@ -153,8 +238,12 @@ impl<'tcx> intravisit::Visitor<'tcx> for Visitor<'_, 'tcx> {
                 let params = params.drain(..).map(|p| p.0).collect();
                 self.params_map.insert(name, params);
             }
+            ItemKind::Const(_, _) if name == "thrd_success" => {
+                self.c11_defined = true;
+            }
             ItemKind::TyAlias(t, _) => {
-                self.ty_alias_map.insert(name, span_to_string(ctx, t.span));
+                let ty = normalize_type(span_to_string(ctx, t.span));
+                self.ty_alias_map.insert(name, ty);
             }
             _ => (),
         }
@@ -163,33 +252,182 @@ impl<'tcx> intravisit::Visitor<'tcx> for Visitor<'_, 'tcx> {
 
     fn visit_expr(&mut self, e: &'tcx Expr<'tcx>) {
         let ctx = self.ctx.unwrap();
+        if let Some(Res::Def(DefKind::Static(_), def_id)) = resolve_path(ctx, e) {
+            if !is_once_arg(ctx, e) {
+                let x = ctx.tcx.item_name(def_id).to_ident_string();
+                self.once_misuses.push((x, e.span));
+            }
+        }
         if let Some(func) = current_function(ctx, e.hir_id) {
             if let Some(path) = expr_to_path(ctx, e) {
                 let ty = type_to_string(unwrap_ptr_from_type(type_of(ctx, e.hir_id)));
-                self.path_type_map.entry(path).or_default().insert(func, ty);
+                self.path_type_map
+                    .entry(path)
+                    .or_default()
+                    .insert(func.clone(), ty);
+            }
+            if !is_thread_arg(ctx, e) {
+                if let Some(slot) = thread_slot(ctx, &func, e) {
+                    self.slot_misuses.push((slot, e.span));
+                }
+            }
+            // data of lock globals, which decide if the globals can be immutable
+            if safe_globals() {
+                if let Some(mutex) = global_lock_of(ctx, e) {
+                    self.unlocked_accesses.push((func.clone(), mutex, e));
+                }
             }
         }
 
         let func_name = || current_function(ctx, e.hir_id).unwrap();
         match e.kind {
             ExprKind::Call(func, args) => {
-                let f = name(func);
+                if return_code(func) == ReturnCode::C11 {
+                    self.c11_used = true;
+                }
+                let f = api_name(func);
+                let args = call_args(func, args);
                 match f.as_deref() {
+                    Some(
+                        "pthread_mutex_timedlock"
+                        | "pthread_rwlock_timedrdlock"
+                        | "pthread_rwlock_timedwrlock",
+                    ) => {
+                        self.timed_lock_used = true;
+                    }
                     Some("pthread_mutex_init" | "pthread_spin_init" | "pthread_rwlock_init") => {
-                        if let Some(mut path) = expr_to_path(ctx, &args[0]) {
+                        if let Some(mut path) = expr_to_path(ctx, args[0]) {
                             if let Some(ExprPathProj::Field(f)) = path.pop() {
                                 let func = func_name();
                                 self.mutex_init_map.insert((func, path, f));
                             }
                         }
                     }
+                    Some("pthread_create") => {
+                        let slot = unwrap_addr(unwrap_cast_recursively(args[0]));
+                        if let Some(slot) = thread_slot(ctx, &func_name(), slot) {
+                            // `thrd_create` takes no attributes
+                            let attr = unwrap_addr(unwrap_cast_recursively(args[1]));
+                            if return_code(func) != ReturnCode::C11
+                                && span_to_string(ctx, attr.span) != "0"
+                            {
+                                let local = matches!(resolve_path(ctx, attr), Some(Res::Local(_)));
+                                let attr = expr_to_path(ctx, attr).filter(|_| local);
+                                let attr = attr.map(|a| a.to_string());
+                                self.thread_attrs
+                                    .push((slot.clone(), func_name(), attr, e.span));
+                            }
+                            self.thread_slots.insert(slot);
+                        }
+                    }
+                    Some("pthread_key_create") => {
+                        if let Some(key) = thread_key(ctx, args[0]) {
+                            let dtor = key_dtor(ctx, args[1]);
+                            self.key_create_map.entry(key).or_default().push((
+                                func_name(),
+                                dtor,
+                                e.span,
+                            ));
+                        }
+                    }
+                    Some("pthread_once") => {
+                        let f = unwrap_cast_recursively(unwrap_call(args[1]));
+                        self.once_callbacks.insert(span_to_string(ctx, f.span));
+                    }
+                    Some("pthread_attr_setstacksize") => {
+                        if let Some(attr) = expr_to_path(ctx, args[0]) {
+                            let size = span_to_string(ctx, args[1].span);
+                            self.stack_size_map
+                                .insert((func_name(), attr.to_string()), size);
+                        }
+                    }
+                    Some(f) if f.starts_with("pthread_attr_set") => {
+                        if let Some(attr) = expr_to_path(ctx, args[0]) {
+                            self.attr_sets.insert((func_name(), attr.to_string()));
+                        }
+                    }
+                    Some("pthread_condattr_setclock") => {
+                        if let Some(attr) = expr_to_path(ctx, args[0]) {
+                            let clock = span_to_string(ctx, args[1].span);
+                            self.condattr_clock_map
+                                .insert((func_name(), attr.to_string()), clock);
+                        }
+                    }
+                    Some("pthread_cond_init") => {
+                        // C11 `cnd_init` takes no attributes
+                        let attr = args.get(1).and_then(|attr| expr_to_path(ctx, attr));
+                        let clock = attr.and_then(|attr| {
+                            self.condattr_clock_map
+                                .get(&(func_name(), attr.to_string()))
+                        });
+                        if let (Some(key), Some(clock)) = (lock_key(ctx, args[0]), clock) {
+                            self.cond_clock_map.insert(key, clock.clone());
+                        }
+                    }
+                    Some(
+                        f @ ("pthread_cond_timedwait"
+                        | "sem_timedwait"
+                        | "pthread_mutex_timedlock"
+                        | "pthread_rwlock_timedrdlock"
+                        | "pthread_rwlock_timedwrlock"),
+                    ) => {
+                        let i = if f == "pthread_cond_timedwait" { 2 } else { 1 };
+                        let t = deadline_name(ctx, args[i]);
+                        self.deadline_set.insert((func_name(), t));
+                    }
+                    Some(
+                        "pthread_mutex_destroy"
+                        | "pthread_spin_destroy"
+                        | "pthread_rwlock_destroy"
+                        | "pthread_cond_destroy",
+                    ) => {
+                        if let Some(path) = expr_to_path(ctx, args[0]) {
+                            self.destroy_set.insert((func_name(), path.base));
+                        }
+                    }
+                    Some(f) => {
+                        // callees may destroy the locks of objects passed to them
+                        for (i, arg) in args.iter().enumerate() {
+                            if let Some(path) = expr_to_path(ctx, arg) {
+                                if path.is_variable() {
+                                    self.pointer_passes.push((
+                                        func_name(),
+                                        path.base,
+                                        f.to_string(),
+                                        i,
+                                    ));
+                                }
+                            }
+                        }
+
+                        // call sites decide the modes of rwlocks held on entry and whether
+                        // callers may hold locks
+                        if function_mutex_map().contains_key(f) {
+                            let args = args.iter().map(|a| expr_to_path(ctx, a)).collect();
+                            let lines = span_lines(ctx, e.span);
+                            self.call_sites
+                                .push((func_name(), f.to_string(), args, lines));
+                        }
+                    }
                     _ => (),
                 }
             }
             ExprKind::Assign(lhs, rhs, _) => {
+                if type_of(ctx, lhs.hir_id).is_unsafe_ptr() {
+                    if let Some(path) = expr_to_path(ctx, lhs) {
+                        let class = pointer_source(ctx, rhs);
+                        self.pointer_assigns.push((func_name(), path, class));
+                    }
+                    // a pointer copied out makes its memory reachable without the lock
+                    if let Some(path) = copied_pointer(ctx, rhs) {
+                        let class = Some(PointerClass::Shared);
+                        self.pointer_assigns.push((func_name(), path, class));
+                    }
+                }
                 match lhs.kind {
                     ExprKind::Path(_) => {
-                        if type_to_string(type_of(ctx, lhs.hir_id)).contains("pthread_mutex_t") {
+                        let ty = normalize_type(type_to_string(type_of(ctx, lhs.hir_id)));
+                        if ty.contains("pthread_mutex_t") {
                             let lhs = span_to_string(ctx, lhs.span);
                             self.lmutex_assign_set.insert((func_name(), lhs));
                         }
@@ -232,8 +470,8 @@ impl<'tcx> intravisit::Visitor<'tcx> for Visitor<'_, 'tcx> {
                                 }
                             }
 
-                            if type_to_string(type_of(ctx, lhs.hir_id)).contains("pthread_mutex_t")
-                            {
+                            let lhs_ty = normalize_type(type_to_string(type_of(ctx, lhs.hir_id)));
+                            if lhs_ty.contains("pthread_mutex_t") {
                                 let rhs = span_to_string(ctx, rhs.span);
                                 self.rmutex_assign_map
                                     .insert((func, rhs), struct_of2(&ty, &f));
@@ -244,23 +482,27 @@ impl<'tcx> intravisit::Visitor<'tcx> for Visitor<'_, 'tcx> {
                 }
                 match rhs.kind {
                     ExprKind::Call(func, args) => {
-                        if let Some(f) = name(func) {
+                        if let Some(f) = api_name(func) {
                             if f == "pthread_mutex_lock"
                                 || f == "pthread_spin_lock"
                                 || f == "pthread_mutex_trylock"
+                                || f == "pthread_mutex_timedlock"
                                 || f == "pthread_spin_trylock"
                                 || f == "pthread_rwlock_rdlock"
                                 || f == "pthread_rwlock_wrlock"
                                 || f == "pthread_rwlock_tryrdlock"
                                 || f == "pthread_rwlock_trywrlock"
+                                || f == "pthread_rwlock_timedrdlock"
+                                || f == "pthread_rwlock_timedwrlock"
                             {
                                 let f = func_name();
                                 let l = span_to_string(ctx, lhs.span);
-                                let line = span_lines(ctx, args[0].span)
+                                let m = call_args(func, args)[0];
+                                let line = span_lines(ctx, m.span)
                                     .drain_filter(|_| true)
                                     .max()
                                     .unwrap();
-                                let (_, g) = normalize_arg(ctx, &args[0]);
+                                let (_, g) = normalize_arg(ctx, m);
                                 self.trylock_map.entry((f, l)).or_default().push((line, g));
                             }
                         }
@@ -292,8 +534,15 @@ impl<'tcx> intravisit::Visitor<'tcx> for Visitor<'_, 'tcx> {
                 },
                 _ => (),
             },
+            ExprKind::Ret(Some(v)) => {
+                if let Some(path) = copied_pointer(ctx, v) {
+                    let class = Some(PointerClass::Shared);
+                    self.pointer_assigns.push((func_name(), path, class));
+                }
+            }
             ExprKind::If(c, _, _) => {
                 if let Some((expr, _)) = read_condition(ctx, c) {
+                    let expr = span_to_string(ctx, expr.span);
                     let f = func_name();
                     let line = span_lines(ctx, c.span)
                         .drain_filter(|_| true)
@@ -318,16 +567,64 @@ struct RewritePass {
     mutex_init_map: BTreeSet<(String, ExprPath, String)>,
     path_type_map: BTreeMap<ExprPath, BTreeMap<String, String>>,
     duration_map: BTreeMap<(String, String, String), String>,
+    cond_clock_map: BTreeMap<String, String>,
+    deadline_used: bool,
     trylock_map: BTreeMap<(String, String, usize), String>,
     params_map: BTreeMap<String, Vec<String>>,
     rmutex_assign_map: BTreeMap<(String, String), String>,
     lmutex_assign_set: BTreeSet<(String, String)>,
+    thread_slots: BTreeSet<ThreadSlot>,
+    thread_structs: BTreeSet<String>,
+    /// `pthread_once_t` statics kept as they are used other than by `pthread_once`
+    kept_onces: BTreeSet<String>,
+    stack_size_map: BTreeMap<(String, String), String>,
+    key_dtor_map: BTreeMap<String, String>,
+    thread_local_map: BTreeMap<String, &'static str>,
+    c11_codes: bool,
+    timed_lock_used: bool,
+    call_sites: Vec<CallSite>,
+    destroy_set: BTreeSet<(String, String)>,
+    pointer_class_map: BTreeMap<String, PointerClass>,
+    /// rwlocks held in both modes by the callers of a function, which are always write-locked
+    write_rwlocks: BTreeSet<String>,
+    /// lock globals kept `static mut` as their data cannot be reached by temporary guards
+    unsafe_globals: BTreeSet<String>,
 
     guard_map: BTreeMap<String, Vec<String>>,
     replaced: BTreeSet<Span>,
 }
 
 impl RewritePass {
+    /// Initializes `place` with `value`; places that may be uninitialized, i.e., locks and
+    /// structs holding them, are written without dropping the garbage in them.
+    fn init_place(
+        &self,
+        ctx: &LateContext<'_>,
+        place_expr: &Expr<'_>,
+        place: &str,
+        value: String,
+    ) -> String {
+        let ty = type_to_string(type_of(ctx, place_expr.hir_id));
+        let mut tys = self
+            .trans_struct_def_map
+            .get(&ty)
+            .cloned()
+            .unwrap_or_default();
+        tys.insert(ty);
+        let uninit = lock_key(ctx, place_expr).map_or(false, |key| uninit_locks().contains(&key))
+            || uninit_locks()
+                .iter()
+                .any(|key| key.split_once('.').map_or(false, |(t, _)| tys.contains(t)));
+        if uninit {
+            format!(
+                "std::ptr::write(std::ptr::addr_of_mut!({}), {})",
+                place, value
+            )
+        } else {
+            format!("{} = {}", place, value)
+        }
+    }
+
     #[allow(clippy::new_ret_no_self)]
     fn new() -> Box<LatePass> {
         Box::new(Self::default())
@@ -342,6 +639,68 @@ impl RewritePass {
             self.use_guard(func.clone(), g.clone());
         }
     }
+
+    fn thread_local_of(
+        &self,
+        ctx: &LateContext<'_>,
+        e: &Expr<'_>,
+    ) -> Option<(String, &'static str)> {
+        if !matches!(resolve_path(ctx, e)?, Res::Def(DefKind::Static(_), _)) {
+            return None;
+        }
+        let x = span_to_string(ctx, e.span);
+        let cell = self.thread_local_map.get(&x)?;
+        Some((x, *cell))
+    }
+
+    /// Rewrites the assignment statement `e` to a place of a thread-local, `lhs`, into a
+    /// temporary borrow of its cell; `op` is the operator of a compound assignment.
+    fn thread_local_assign(
+        &mut self,
+        ctx: &LateContext<'_>,
+        e: &Expr<'_>,
+        lhs: &Expr<'_>,
+        rhs: &Expr<'_>,
+        op: Option<BinOpKind>,
+    ) -> bool {
+        let mut base = lhs;
+        while let ExprKind::Field(b, _) | ExprKind::Index(b, _) = base.kind {
+            base = b;
+        }
+        let (x, cell) = some_or!(self.thread_local_of(ctx, base), return false);
+        if temporary_access(ctx, base) != Some(TemporaryAccess::Write) {
+            return false;
+        }
+        let set = match op {
+            _ if cell != "Cell" => {
+                let place = span_to_string(ctx, lhs.span.with_lo(base.span.hi()));
+                let op = op.map_or("", |op| op.as_str());
+                format!(
+                    "{}.with(|cell| (*cell.borrow_mut()){} {}= value)",
+                    x, place, op
+                )
+            }
+            Some(op) => format!(
+                "{}.with(|cell| cell.set(cell.get() {} value))",
+                x,
+                op.as_str()
+            ),
+            None => format!("{}.with(|cell| cell.set(value))", x),
+        };
+        self.replaced.insert(lhs.span);
+        bind_value(ctx, e, rhs, None, &set);
+        true
+    }
+
+    /// Checks if `e` is a `pthread_t` slot retyped to hold a `JoinHandle`.
+    fn is_thread_slot<'tcx>(
+        &self,
+        ctx: &LateContext<'tcx>,
+        func: &str,
+        e: &'tcx Expr<'tcx>,
+    ) -> bool {
+        thread_slot(ctx, func, e).map_or(false, |slot| self.thread_slots.contains(&slot))
+    }
 }
 
 impl LintPass for RewritePass {
@@ -364,10 +723,124 @@ impl<'tcx> LateLintPass<'tcx> for RewritePass {
         self.init_map = visitor.init_map;
         self.mutex_init_map = visitor.mutex_init_map;
         self.path_type_map = visitor.path_type_map;
+        self.pointer_class_map = classify_pointers(&visitor.pointer_assigns, &self.path_type_map);
         self.duration_map = visitor.duration_map;
+        self.cond_clock_map = visitor.cond_clock_map;
+        self.deadline_used = visitor
+            .deadline_set
+            .iter()
+            .any(|(f, t)| !self.is_offset_deadline(f, t));
         self.params_map = visitor.params_map;
         self.rmutex_assign_map = visitor.rmutex_assign_map;
         self.lmutex_assign_set = visitor.lmutex_assign_set;
+        // attributes other than the stack size, or set out of sight, cannot be given to `Builder`
+        for (slot, func, attr, span) in &visitor.thread_attrs {
+            let translatable = attr.as_ref().map_or(false, |attr| {
+                !visitor.attr_sets.contains(&(func.clone(), attr.clone()))
+            });
+            if !translatable && visitor.thread_slots.remove(slot) {
+                ctx.sess().span_warn(
+                    *span,
+                    "thread is created with attributes other than the stack size; its `pthread_t` is not translated",
+                );
+            }
+        }
+        for (slot, span) in &visitor.slot_misuses {
+            if visitor.thread_slots.remove(slot) {
+                ctx.sess().span_warn(
+                    *span,
+                    "thread is used other than by create, join and detach; its `pthread_t` is not translated",
+                );
+            }
+        }
+        for (x, span) in &visitor.once_misuses {
+            let is_once = self
+                .global_def_map
+                .get(x)
+                .map_or(false, |(t, _)| t == "pthread_once_t");
+            if is_once && self.kept_onces.insert(x.clone()) {
+                ctx.sess().span_warn(
+                    *span,
+                    "once is used other than by `pthread_once`; its `pthread_once_t` is not translated",
+                );
+            }
+        }
+        self.thread_structs = visitor
+            .thread_slots
+            .iter()
+            .filter_map(|slot| match slot {
+                ThreadSlot::Field(s, _) => Some(s.clone()),
+                _ => None,
+            })
+            .collect();
+        self.thread_slots = visitor.thread_slots;
+        self.stack_size_map = visitor.stack_size_map;
+        self.thread_local_map = visitor.thread_local_map;
+        self.c11_codes = visitor.c11_used && !visitor.c11_defined;
+        self.timed_lock_used = visitor.timed_lock_used;
+        self.call_sites = visitor.call_sites;
+        // a function receives a write guard, which also permits reads, when its callers hold
+        // an rwlock in both modes, so the read-locking callers write-lock it instead
+        let write_rwlocks = function_mutex_map()
+            .iter()
+            .flat_map(|(f, s)| {
+                s.entry_rwlock
+                    .iter()
+                    .filter(|m| self.entry_rwlock_modes(f, m, &mut BTreeSet::new()).len() > 1)
+                    .map(|m| self.lock_key_of(f, m))
+                    .collect::<Vec<_>>()
+            })
+            .collect();
+        self.write_rwlocks = write_rwlocks;
+        for (func, mutex, e) in &visitor.unlocked_accesses {
+            let lines = span_lines(ctx, e.span);
+            if is_held(func, mutex, &lines) || self.is_temporary(ctx, func, mutex, e) {
+                continue;
+            }
+            if self.unsafe_globals.insert(mutex.base.clone()) {
+                ctx.sess().span_warn(
+                    e.span,
+                    format!(
+                        "data of `{0}` is borrowed or may be accessed while `{0}` is held; `{0}` stays `static mut`",
+                        mutex.base
+                    ),
+                );
+            }
+        }
+        self.destroy_set = visitor.destroy_set;
+        loop {
+            let destroyed: Vec<_> = visitor
+                .pointer_passes
+                .iter()
+                .filter(|(caller, x, callee, i)| {
+                    let param = self.params_map.get(callee).and_then(|ps| ps.get(*i));
+                    !self.destroy_set.contains(&(caller.clone(), x.clone()))
+                        && param.map_or(false, |p| {
+                            self.destroy_set.contains(&(callee.clone(), p.clone()))
+                        })
+                })
+                .map(|(caller, x, _, _)| (caller.clone(), x.clone()))
+                .collect();
+            if destroyed.is_empty() {
+                break;
+            }
+            self.destroy_set.extend(destroyed);
+        }
+        // keys created by a single call run once, in `main` or a `pthread_once` callback
+        for (k, v) in &visitor.key_create_map {
+            match &v[..] {
+                [(f, Some(dtor), _)] if f == "main_0" || visitor.once_callbacks.contains(f) => {
+                    self.key_dtor_map.insert(k.clone(), dtor.clone());
+                }
+                _ => ctx.sess().span_warn(
+                    v[0].2,
+                    format!(
+                        "pthread key `{}` is not created once in `main` or a `pthread_once` callback with a function or null destructor and is not translated",
+                        k
+                    ),
+                ),
+            }
+        }
 
         let mut map: BTreeMap<_, _> = self
             .struct_def_map
@@ -410,13 +883,43 @@ impl<'tcx> LateLintPass<'tcx> for RewritePass {
             add_replacement(
                 ctx,
                 span,
-                "use std::{sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard, Condvar}, time::Duration};\nu".to_string(),
+                "use std::{sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard, Condvar, Once, Barrier}, time::Duration};\nu".to_string(),
             );
+            let mut defs = String::new();
+            if mutex_kind_map().values().any(|k| *k != MutexKind::Spin) {
+                defs.push_str(PTHREAD_MUTEX);
+            }
+            if mutex_kind_map().values().any(|k| *k == MutexKind::Spin) {
+                defs.push_str(SPIN_LOCK);
+            }
+            if self.trans_struct_def_map.contains_key("sem_t") {
+                defs.push_str(SEMAPHORE);
+            }
+            if !self.key_dtor_map.is_empty() {
+                defs.push_str(THREAD_SPECIFIC);
+            }
+            if self.c11_codes {
+                defs.push_str(THRD_CODES);
+            }
+            if self.deadline_used {
+                defs.push_str(DEADLINE_TIMEOUT);
+            }
+            if self.timed_lock_used {
+                defs.push_str(TIMED_LOCK);
+            }
+            if !defs.is_empty() {
+                let span = m.spans.inner_span.shrink_to_hi();
+                add_replacement(ctx, span, defs);
+            }
         }
     }
 
     fn check_item(&mut self, ctx: &LateContext<'tcx>, i: &'tcx Item<'tcx>) {
         match &i.kind {
+            // pointers to semaphore slots become pointers to the retyped slots
+            ItemKind::TyAlias(_, _) if i.ident.name.to_ident_string() == "sem_t" => {
+                add_replacement(ctx, i.span, "pub type sem_t = Semaphore;".to_string());
+            }
             ItemKind::Impl(im) => {
                 let t = im.of_trait.as_ref().map(|t| path_to_string(t.path));
                 if t == Some("Copy".to_string()) {
@@ -424,20 +927,25 @@ impl<'tcx> LateLintPass<'tcx> for RewritePass {
                         TyKind::Path(QPath::Resolved(_, p)) => path_to_string(p),
                         _ => unreachable!(),
                     };
+                    let atomic = |s: &String| {
+                        atomic_map()
+                            .keys()
+                            .any(|k| k.split_once('.').map_or(false, |(t, _)| t == s))
+                    };
                     if let Some(map) = self.trans_struct_def_map.get(&s) {
-                        if map.iter().any(|t| {
-                            t == "pthread_mutex_t"
-                                || t == "pthread_spinlock_t"
-                                || t == "pthread_rwlock_t"
-                                || t == "pthread_cond_t"
-                        }) {
-                            let span = i.span;
-                            add_replacement(
-                                ctx,
-                                span.with_lo(span.lo() - BytePos(9))
-                                    .with_hi(span.hi() + BytePos(9)),
-                                "".to_string(),
-                            );
+                        if self.thread_structs.contains(&s)
+                            || atomic(&s)
+                            || map.iter().any(|t| {
+                                is_sync_type(t) || self.thread_structs.contains(t) || atomic(t)
+                            })
+                        {
+                            match derive_attribute(i.span) {
+                                Some(span) => add_replacement(ctx, span, "".to_string()),
+                                None => ctx.sess().span_warn(
+                                    i.span,
+                                    format!("`Copy` impl of `{}` is not removed", s),
+                                ),
+                            }
                         }
                     }
                 }
@@ -459,11 +967,26 @@ impl<'tcx> LateLintPass<'tcx> for RewritePass {
                     .collect();
                 for f in fs.iter() {
                     let name = f.ident.name.to_ident_string();
-                    let typ = span_to_string(ctx, f.ty.span);
+                    let typ = normalize_type(span_to_string(ctx, f.ty.span));
                     if typ == "pthread_cond_t" {
                         add_replacement(ctx, f.ty.span, "Condvar".to_string());
                         continue;
                     }
+                    if typ == "sem_t" {
+                        add_replacement(ctx, f.ty.span, "Semaphore".to_string());
+                        continue;
+                    }
+                    if typ == "pthread_barrier_t" {
+                        add_replacement(ctx, f.ty.span, BARRIER.to_string());
+                        continue;
+                    }
+                    if self
+                        .thread_slots
+                        .contains(&ThreadSlot::Field(s.clone(), name.clone()))
+                    {
+                        add_replacement(ctx, f.ty.span, JOIN_HANDLE.to_string());
+                        continue;
+                    }
                     if v.iter().any(|(x, _, _)| *x == name) {
                         let span = f.span.with_hi(f.span.hi() + BytePos(1));
                         add_replacement(ctx, span, "".to_string());
@@ -474,7 +997,7 @@ impl<'tcx> LateLintPass<'tcx> for RewritePass {
                         || typ == "pthread_rwlock_t"
                     {
                         let lock_ty = if typ != "pthread_rwlock_t" {
-                            "Mutex"
+                            lock_type_of(format!("{}.{}", s, name), "Mutex")
                         } else {
                             "RwLock"
                         };
@@ -491,7 +1014,14 @@ impl<'tcx> LateLintPass<'tcx> for RewritePass {
                         let st_name = struct_of2(&s, &name);
                         add_replacement(ctx, f.ty.span, format!("{}<{}>", lock_ty, st_name));
                         let st_body = join(pfs, ", ");
-                        let st = format!("\npub struct {} {{ {} }}", st_name, st_body);
+                        let fs = v
+                            .iter()
+                            .filter(|(_, _, m)| *m == name)
+                            .map(|(x, t, _)| (format!("{}.{}", s, x), t))
+                            .collect();
+                        let rwlock = typ == "pthread_rwlock_t";
+                        let send = self.send_impls(ctx, i.span, &st_name, fs, rwlock);
+                        let st = format!("\npub struct {} {{ {} }}{}", st_name, st_body, send);
                         new_structs.push_str(&st);
                     }
                 }
@@ -499,20 +1029,93 @@ impl<'tcx> LateLintPass<'tcx> for RewritePass {
                     add_replacement(ctx, i.span.shrink_to_hi(), new_structs);
                 }
             }
-            ItemKind::Static(t, _, _) => {
+            ItemKind::Static(t, _, b) => {
                 let name = i.ident.name.to_ident_string();
-                let typ = span_to_string(ctx, t.span);
+                let typ = normalize_type(span_to_string(ctx, t.span));
+
+                // thread-local
+                if let Some(cell) = self.thread_local_map.get(&name) {
+                    let init = hid_to_string(ctx, b.hir_id);
+                    let new_i = format!(
+                        "thread_local! {{ pub static {0}: std::cell::{1}<{2}> = std::cell::{1}::new({3}); }}",
+                        name, cell, typ, init
+                    );
+                    add_replacement(ctx, i.span, new_i);
+                    remove_attributes(ctx, i);
+                    return;
+                }
 
                 // condvar
                 if typ == "pthread_cond_t" {
-                    let new_i = format!("pub static mut {}: Condvar = Condvar::new();", name);
+                    let new_i = format!(
+                        "pub {} {}: Condvar = Condvar::new();",
+                        static_of(safe_globals()),
+                        name
+                    );
+                    add_replacement(ctx, i.span, new_i);
+                    remove_attributes(ctx, i);
+                    return;
+                }
+
+                // thread-specific key
+                if let Some(dtor) = self.key_dtor_map.get(&name) {
+                    let new_i = format!(
+                        "thread_local! {{ pub static {}: ThreadSpecific = ThreadSpecific::new({}); }}",
+                        name, dtor
+                    );
+                    add_replacement(ctx, i.span, new_i);
+                    remove_attributes(ctx, i);
+                    return;
+                }
+
+                // once
+                if typ == "pthread_once_t" && !self.kept_onces.contains(&name) {
+                    let new_i = format!("pub static {}: Once = Once::new();", name);
+                    add_replacement(ctx, i.span, new_i);
+                    remove_attributes(ctx, i);
+                    return;
+                }
+
+                // semaphore
+                if typ == "sem_t" {
+                    let new_i = format!("pub static mut {}: Semaphore = Semaphore::new(0);", name);
+                    add_replacement(ctx, i.span, new_i);
+                    remove_attributes(ctx, i);
+                    return;
+                }
+
+                // barrier
+                if typ == "pthread_barrier_t" {
+                    let new_i = format!("pub static mut {}: {} = None;", name, BARRIER);
+                    add_replacement(ctx, i.span, new_i);
+                    remove_attributes(ctx, i);
+                    return;
+                }
+
+                // thread handle
+                if self
+                    .thread_slots
+                    .contains(&ThreadSlot::Global(name.clone()))
+                {
+                    let new_i = if let TyKind::Array(_, l) = &t.kind {
+                        let l = hid_to_string(ctx, l.hir_id());
+                        format!(
+                            "const {0}_none: {1} = None;
+pub static mut {0}: [{1}; {2}] = [{0}_none; {2}];",
+                            name, JOIN_HANDLE, l
+                        )
+                    } else {
+                        format!("pub static mut {}: {} = None;", name, JOIN_HANDLE)
+                    };
                     add_replacement(ctx, i.span, new_i);
                     remove_attributes(ctx, i);
                     return;
                 }
 
-                // global or array
-                if global_mutex_map().get(&name).is_some() || array_mutex_map().get(&name).is_some()
+                // global or array, or lock deleted by promotion
+                if global_mutex_map().get(&name).is_some()
+                    || array_mutex_map().get(&name).is_some()
+                    || promoted_map().values().any(|m| *m == name)
                 {
                     add_replacement(ctx, i.span, "".to_string());
                     remove_attributes(ctx, i);
@@ -536,17 +1139,31 @@ impl<'tcx> LateLintPass<'tcx> for RewritePass {
                     }
                     let struct_name = struct_of(&name);
                     let lock_ty = if typ != "pthread_rwlock_t" {
-                        "Mutex"
+                        lock_type_of(name.clone(), "Mutex")
                     } else {
                         "RwLock"
                     };
+                    let safe = self.is_safe_global(&name);
+                    let fs = global_mutex_map()
+                        .iter()
+                        .filter(|(_, m)| **m == name)
+                        .map(|(x, _)| (x.clone(), &self.global_def_map.get(x).unwrap().0))
+                        .collect();
+                    let rwlock = typ == "pthread_rwlock_t";
+                    let send = self.send_impls(ctx, i.span, &struct_name, fs, rwlock);
                     let code = format!(
                         "
-pub struct {0} {{ {1}}}
-pub static mut {2}: {3}<{0}> = {3}::new(
+pub struct {0} {{ {1}}}{5}
+pub {6} {2}: {3}<{0}> = {3}::new(
     {0} {{ {4}}}
 );",
-                        struct_name, decl, name, lock_ty, init
+                        struct_name,
+                        decl,
+                        name,
+                        lock_ty,
+                        init,
+                        send,
+                        static_of(safe)
                     );
                     add_replacement(ctx, i.span, code);
                     remove_attributes(ctx, i);
@@ -555,13 +1172,13 @@ pub static mut {2}: {3}<{0}> = {3}::new(
 
                 // mutex array
                 if let TyKind::Array(t, _) = t.kind {
-                    let ty = span_to_string(ctx, t.span);
+                    let ty = normalize_type(span_to_string(ctx, t.span));
                     if ty == "pthread_mutex_t"
                         || ty == "pthread_spinlock_t"
                         || ty == "pthread_rwlock_t"
                     {
                         let lock_ty = if ty != "pthread_rwlock_t" {
-                            "Mutex"
+                            lock_type_of(name.clone(), "Mutex")
                         } else {
                             "RwLock"
                         };
@@ -601,12 +1218,23 @@ pub static mut {2}: {3}<{0}> = {3}::new(
                                 .collect(),
                             ",",
                         );
+                        let safe = self.is_safe_global(&name);
+                        let fs = v.iter().map(|(x, t, _)| ((*x).clone(), t)).collect();
+                        let rwlock = ty == "pthread_rwlock_t";
+                        let send = self.send_impls(ctx, i.span, &struct_name, fs, rwlock);
                         let code = format!(
                             "
-pub struct {0} {{ {1} }}
-pub static mut {2}: [{3}<{0}>; {4}] = [{5}
+pub struct {0} {{ {1} }}{6}
+pub {7} {2}: [{3}<{0}>; {4}] = [{5}
 ];",
-                            struct_name, decl, name, lock_ty, len, init
+                            struct_name,
+                            decl,
+                            name,
+                            lock_ty,
+                            len,
+                            init,
+                            send,
+                            static_of(safe)
                         );
                         add_replacement(ctx, i.span, code);
                         remove_attributes(ctx, i);
@@ -629,16 +1257,26 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
         match kind {
             intravisit::FnKind::ItemFn(id, _, _) => {
                 let name = id.name.to_ident_string();
-                let empty = vec![];
-                let (entry, ret) = if let Some(fs) = function_mutex_map().get(&name) {
-                    let FunctionSummary {
-                        entry_mutex,
-                        ret_mutex,
-                        ..
-                    } = fs;
-                    (entry_mutex, ret_mutex)
-                } else {
-                    (&empty, &empty)
+                let empty = FunctionSummary::default();
+                let fs = function_mutex_map().get(&name).unwrap_or(&empty);
+                let entry = fs.entry_locks();
+                let ret = fs.ret_locks();
+
+                let guard_type = |m: &ExprPath, held_on_entry: bool| {
+                    if held_on_entry && fs.entry_rwlock.contains(m) {
+                        let modes = self.entry_rwlock_modes(&name, m, &mut BTreeSet::new());
+                        if modes.contains(&false) && !self.is_write_rwlock(&name, m) {
+                            "RwLockReadGuard"
+                        } else {
+                            "RwLockWriteGuard"
+                        }
+                    } else if fs.ret_rdlock.contains(m) && !self.is_write_rwlock(&name, m) {
+                        "RwLockReadGuard"
+                    } else if fs.ret_rdlock.contains(m) || fs.ret_wrlock.contains(m) {
+                        "RwLockWriteGuard"
+                    } else {
+                        self.guard_type(&name, m)
+                    }
                 };
 
                 if !entry.is_empty() {
@@ -646,8 +1284,9 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                         .iter()
                         .map(|m| {
                             format!(
-                                "mut {}: MutexGuard<'static, {}>",
+                                "mut {}: {}<'static, {}>",
                                 m.guard(),
+                                guard_type(m, true),
                                 self.struct_of_path(&name, m)
                             )
                         })
@@ -675,9 +1314,10 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                     if let FnRetTy::Return(t) = decl.output {
                         ret_types.push(span_to_string(ctx, t.span));
                     }
-                    for m in ret {
+                    for m in &ret {
                         ret_types.push(format!(
-                            "MutexGuard<'static, {}>",
+                            "{}<'static, {}>",
+                            guard_type(m, false),
                             self.struct_of_path(&name, m)
                         ));
                     }
@@ -716,12 +1356,9 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
         match kind {
             intravisit::FnKind::ItemFn(id, _, _) => {
                 let name = id.name.to_ident_string();
-                let empty = vec![];
-                let entry = if let Some(fs) = function_mutex_map().get(&name) {
-                    &fs.entry_mutex
-                } else {
-                    &empty
-                };
+                let entry = function_mutex_map()
+                    .get(&name)
+                    .map_or(vec![], FunctionSummary::entry_locks);
                 let entry: BTreeSet<_> = entry.iter().map(|m| m.guard()).collect();
                 let mut guards = self.guard_map.get(&name).cloned().unwrap_or_default();
                 guards.sort();
@@ -750,7 +1387,7 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                 pat, ty: Some(ty), ..
             }) => {
                 let x = span_to_string(ctx, pat.span).replace("mut ", "");
-                let ty = span_to_string(ctx, ty.span);
+                let ty = normalize_type(span_to_string(ctx, ty.span));
                 if ty.contains("pthread_mutex_t") {
                     if self.lmutex_assign_set.contains(&(func.clone(), x.clone())) {
                         add_replacement(ctx, s.span, format!("let mut {};", x));
@@ -768,6 +1405,29 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                     );
                 } else if ty.contains("pthread_cond_t") {
                     add_replacement(ctx, s.span, format!("let mut {} = Condvar::new();", x));
+                } else if ty == "sem_t" {
+                    add_replacement(ctx, s.span, format!("let mut {} = Semaphore::new(0);", x));
+                } else if ty == "pthread_barrier_t" {
+                    let new_s = format!("let mut {}: {} = None;", x, BARRIER);
+                    add_replacement(ctx, s.span, new_s);
+                } else if self
+                    .thread_slots
+                    .contains(&ThreadSlot::Local(func, x.clone()))
+                {
+                    let new_s = if let Some(l) = ty
+                        .strip_prefix('[')
+                        .and_then(|t| t.strip_suffix(']'))
+                        .and_then(|t| t.split_once(';'))
+                        .map(|(_, l)| l.trim())
+                    {
+                        format!(
+                            "let mut {}: [{}; {2}] = [(); {2}].map(|_| None);",
+                            x, JOIN_HANDLE, l
+                        )
+                    } else {
+                        format!("let mut {}: {} = None;", x, JOIN_HANDLE)
+                    };
+                    add_replacement(ctx, s.span, new_s);
                 }
             }
             _ => (),
@@ -808,10 +1468,39 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
             }
             false
         };
+        // the depth at which an attributed mutex is held before its lock or unlock call
+        let held_depth = |mutex: &ExprPath| {
+            let lines = some_or!(func_summary().mutex_depth.get(mutex), return 0);
+            span_lines(ctx, e.span)
+                .iter()
+                .filter_map(|l| lines.get(l).copied())
+                .max()
+                .unwrap_or(0)
+        };
+        if func_name_opt.is_some() && self.copy_value(ctx, &func_name(), e, &is_protected) {
+            return;
+        }
         match &e.kind {
             ExprKind::Call(func, args) => {
-                let f = name(func);
-                let arg = |idx| normalize_arg(ctx, &args[idx]);
+                let code = return_code(func);
+                let c11 = code == ReturnCode::C11;
+                let f = api_name(func);
+                let args = call_args(func, args);
+                let arg = |idx: usize| normalize_arg(ctx, args[idx]);
+
+                // lock deleted by promotion
+                let is_lock_api = f.as_ref().map_or(false, |f| {
+                    f.starts_with("pthread_mutex_")
+                        || f.starts_with("pthread_spin_")
+                        || f.starts_with("pthread_rwlock_")
+                });
+                if let Some(m) = args.first().and_then(|a| lock_key(ctx, a)) {
+                    if is_lock_api && promoted_map().values().any(|x| *x == m) {
+                        add_replacement(ctx, e.span, "0".to_string());
+                        return;
+                    }
+                }
+
                 match f.as_deref() {
                     Some("pthread_mutex_init" | "pthread_spin_init" | "pthread_rwlock_init") => {
                         let lock_ty = if f.as_ref().unwrap() != "pthread_rwlock_init" {
@@ -819,7 +1508,7 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                         } else {
                             "RwLock"
                         };
-                        let mut path = expr_to_path(ctx, &args[0]).unwrap();
+                        let mut path = expr_to_path(ctx, args[0]).unwrap();
                         if self.global_def_map.contains_key(&path.base) || path.is_variable() {
                             add_replacement(ctx, e.span, "0".to_string());
                         } else {
@@ -830,6 +1519,7 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                                 }
                             };
                             let typ = self.get_type(&path, &func_name());
+                            let lock_ty = lock_type_of(format!("{}.{}", typ, f), lock_ty);
                             let empty = BTreeMap::new();
                             let map = struct_mutex_map().get(typ).unwrap_or(&empty);
                             let init = join(
@@ -852,20 +1542,101 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                                 ", ",
                             );
                             let st = format!("{} {{ {} }}", struct_of2(typ, &f), init);
-                            let m = unwrap_addr(unwrap_cast_recursively(&args[0]));
+                            let m = unwrap_addr(unwrap_cast_recursively(args[0]));
                             let m = span_to_string(ctx, m.span);
-                            let new_init = if in_assignment(ctx, e, false) {
-                                format!("{{ {} = {}::new({}); 0 }}", m, lock_ty, st)
+                            let new_init = self.init_place(
+                                ctx,
+                                args[0],
+                                &m,
+                                format!("{}::new({})", lock_ty, st),
+                            );
+                            let new_init = if result_used(ctx, e) {
+                                format!("{{ {}; {} }}", new_init, ok_code(code))
                             } else {
-                                format!("{} = {}::new({})", m, lock_ty, st)
+                                new_init
                             };
                             add_replacement(ctx, e.span, new_init);
                         }
                     }
                     Some(
-                        "pthread_mutex_destroy" | "pthread_spin_destroy" | "pthread_rwlock_destroy",
+                        "pthread_mutex_destroy"
+                        | "pthread_spin_destroy"
+                        | "pthread_rwlock_destroy"
+                        | "pthread_cond_destroy",
                     ) => {
-                        add_replacement(ctx, e.span, "0".to_string());
+                        let new_e = destroy_lock(ctx, &func_name(), e, args[0]);
+                        add_replacement(ctx, e.span, new_e);
+                    }
+                    Some(f @ ("memset" | "memcpy" | "memmove")) => {
+                        let p = unwrap_cast_recursively(args[0]);
+                        let ty = type_to_string(unwrap_ptr_from_type(type_of(ctx, p.hir_id)));
+                        if !self.contains_lock(&ty) {
+                            return;
+                        }
+                        let is_memset = f == "memset";
+                        let zero = span_to_string(ctx, unwrap_cast_recursively(args[1]).span);
+                        if !is_size_of(ctx, args[2], &ty)
+                            || (is_memset && zero != "0")
+                            || result_used(ctx, e)
+                        {
+                            ctx.sess().span_warn(
+                                e.span,
+                                format!("`{}` on `{}` containing locks is not translated", f, ty),
+                            );
+                            return;
+                        }
+                        if let Some(x) = is_memset.then(|| self.unzeroable_array(&ty)).flatten() {
+                            ctx.sess().span_warn(
+                                e.span,
+                                format!(
+                                    "`memset` on `{}` is not translated; the array `{}` cannot be zeroed",
+                                    ty, x
+                                ),
+                            );
+                            return;
+                        }
+                        let value = if is_memset {
+                            self.fresh_struct(&ty, "", &|_, t, _| zero_value(t))
+                        } else {
+                            let src = unwrap_cast_recursively(args[1]);
+                            let src = match &src.kind {
+                                ExprKind::AddrOf(_, _, src) => *src,
+                                _ => src,
+                            };
+                            let func = func_name();
+                            self.copy_struct(ctx, &func, &ty, src, &is_protected)
+                        };
+                        let new_e = match &p.kind {
+                            ExprKind::AddrOf(_, _, dst) => {
+                                self.init_place(ctx, dst, &span_to_string(ctx, dst.span), value)
+                            }
+                            _ => format!(
+                                "std::ptr::write({}, {})",
+                                span_to_string(ctx, p.span),
+                                value
+                            ),
+                        };
+                        add_replacement(ctx, e.span, new_e);
+                        self.replaced.insert(e.span);
+                    }
+                    Some("free") => {
+                        let p = unwrap_cast_recursively(args[0]);
+                        let ty = type_to_string(unwrap_ptr_from_type(type_of(ctx, p.hir_id)));
+                        let has_lock = uninit_locks()
+                            .iter()
+                            .any(|key| key.split_once('.').map_or(false, |(t, _)| t == ty));
+                        let destroyed = expr_to_path(ctx, p).map_or(false, |path| {
+                            self.destroy_set.contains(&(func_name(), path.base))
+                        });
+                        if has_lock && !destroyed {
+                            ctx.sess().span_warn(
+                                e.span,
+                                format!(
+                                    "`{}` is freed without destroying its locks; the locks are leaked",
+                                    ty
+                                ),
+                            );
+                        }
                     }
                     Some(
                         "pthread_mutex_lock"
@@ -873,49 +1644,165 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                         | "pthread_rwlock_rdlock"
                         | "pthread_rwlock_wrlock",
                     ) => {
+                        let path = expr_to_path(ctx, args[0]).unwrap();
                         let lock_method = match f.as_deref().unwrap() {
-                            "pthread_rwlock_rdlock" => "read",
-                            "pthread_rwlock_wrlock" => "write",
+                            "pthread_rwlock_rdlock"
+                                if !self.is_write_rwlock(&func_name(), &path) =>
+                            {
+                                "read"
+                            }
+                            "pthread_rwlock_rdlock" | "pthread_rwlock_wrlock" => "write",
                             _ => "lock",
                         };
                         let (arg, guard) = arg(0);
-                        if in_assignment(ctx, e, false) {
-                            let new_e = format!(
-                                "{{
-        {0}_opt = {1}.{2}().ok();
-        if {0}_opt.is_some() {{ 0 }} else {{ libc::ENOTRECOVERABLE }}
+                        if self.is_attributed(&func_name(), &path) && held_depth(&path) > 0 {
+                            // the owner relocks through its guard
+                            let new_e = if code == ReturnCode::Errno || !result_used(ctx, e) {
+                                format!("{}.relock()", guard)
+                            } else {
+                                format!(
+                                    "if {}.relock() == 0 {{ {} }} else {{ {} }}",
+                                    guard,
+                                    ok_code(code),
+                                    ret_code(code, "EDEADLK")
+                                )
+                            };
+                            add_replacement(ctx, e.span, new_e);
+                            self.use_guard(func_name(), guard);
+                        } else if self.is_attributed(&func_name(), &path) {
+                            // an errorcheck mutex relocked at runtime fails without a guard
+                            let (new_e, guard) = if !result_used(ctx, e) {
+                                let errorcheck = self.mutex_kind(&func_name(), &path)
+                                    == Some(MutexKind::ErrorCheck);
+                                if errorcheck && self.may_relock(&func_name(), &path) {
+                                    ctx.sess().span_warn(
+                                        e.span,
+                                        "unchecked lock of an errorcheck mutex that callers may hold is not translated",
+                                    );
+                                    return;
+                                }
+                                (format!("{} = {}.lock().unwrap()", guard, arg), guard)
+                            } else if in_assignment(ctx, e, false) {
+                                // results assigned to variables are matched by later tests on them
+                                let new_e = format!(
+                                    "{{
+        {0}_opt = {1}.lock().ok();
+        if {0}_opt.is_some() {{ {2} }} else {{ {3} }}
     }}",
-                                guard, arg, lock_method
-                            );
+                                    guard,
+                                    arg,
+                                    ok_code(code),
+                                    ret_code(code, "EDEADLK")
+                                );
+                                (new_e, format!("{}_opt", guard))
+                            } else {
+                                let new_e = format!(
+                                    "match {1}.lock() {{
+        Ok({0}_tmp) => {{ {0} = {0}_tmp; {2} }}
+        Err(_) => {3},
+    }}",
+                                    guard,
+                                    arg,
+                                    ok_code(code),
+                                    ret_code(code, "EDEADLK")
+                                );
+                                (new_e, guard)
+                            };
                             add_replacement(ctx, e.span, new_e);
-                            self.use_guard(func_name(), format!("{}_opt", guard));
-                        } else {
-                            add_replacement(
-                                ctx,
-                                e.span,
-                                format!("{} = {}.{}().unwrap()", guard, arg, lock_method),
-                            );
                             self.use_guard(func_name(), guard);
-                        }
-                    }
-                    Some(
+                        } else if result_used(ctx, e) {
+                            // results assigned to variables are matched by later tests on them
+                            let (new_e, guard) = if in_assignment(ctx, e, false) {
+                                let new_e = format!(
+                                    "{{
+        {0}_opt = {1}.{2}().ok();
+        if {0}_opt.is_some() {{ {3} }} else {{ {4} }}
+    }}",
+                                    guard,
+                                    arg,
+                                    lock_method,
+                                    ok_code(code),
+                                    ret_code(code, "ENOTRECOVERABLE")
+                                );
+                                (new_e, format!("{}_opt", guard))
+                            } else {
+                                // a poisoned lock is still acquired, like a robust mutex whose
+                                // owner died, so the guard is initialized on both branches
+                                let new_e = format!(
+                                    "match {1}.{2}() {{
+        Ok({0}_tmp) => {{ {0} = {0}_tmp; {3} }}
+        Err({0}_err) => {{ {0} = {0}_err.into_inner(); {4} }}
+    }}",
+                                    guard,
+                                    arg,
+                                    lock_method,
+                                    ok_code(code),
+                                    ret_code(code, "EOWNERDEAD")
+                                );
+                                (new_e, guard)
+                            };
+                            add_replacement(ctx, e.span, new_e);
+                            self.use_guard(func_name(), guard);
+                        } else {
+                            let new_e = format!("{} = {}.{}().unwrap()", guard, arg, lock_method);
+                            add_replacement(ctx, e.span, new_e);
+                            self.use_guard(func_name(), guard);
+                        }
+                    }
+                    Some(
                         "pthread_mutex_trylock"
                         | "pthread_spin_trylock"
                         | "pthread_rwlock_tryrdlock"
                         | "pthread_rwlock_trywrlock",
                     ) => {
+                        let write = self.is_write_rwlock_arg(ctx, &func_name(), args[0]);
                         let lock_method = match f.as_deref().unwrap() {
-                            "pthread_rwlock_tryrdlock" => "try_read",
-                            "pthread_rwlock_trywrlock" => "try_write",
+                            "pthread_rwlock_tryrdlock" if !write => "try_read",
+                            "pthread_rwlock_tryrdlock" | "pthread_rwlock_trywrlock" => "try_write",
                             _ => "try_lock",
                         };
                         let (arg, guard) = arg(0);
                         let new_e = format!(
                             "{{
         {0}_opt = {1}.{2}().ok();
-        if {0}_opt.is_some() {{ 0 }} else {{ libc::EBUSY }}
+        if {0}_opt.is_some() {{ {3} }} else {{ {4} }}
+    }}",
+                            guard,
+                            arg,
+                            lock_method,
+                            ok_code(code),
+                            ret_code(code, "EBUSY")
+                        );
+                        add_replacement(ctx, e.span, new_e);
+                        self.use_guard(func_name(), format!("{}_opt", guard));
+                    }
+                    Some(
+                        "pthread_mutex_timedlock"
+                        | "pthread_rwlock_timedrdlock"
+                        | "pthread_rwlock_timedwrlock",
+                    ) => {
+                        let write = self.is_write_rwlock_arg(ctx, &func_name(), args[0]);
+                        let lock_method = match f.as_deref().unwrap() {
+                            "pthread_rwlock_timedrdlock" if !write => "try_read",
+                            "pthread_rwlock_timedrdlock" | "pthread_rwlock_timedwrlock" => {
+                                "try_write"
+                            }
+                            _ => "try_lock",
+                        };
+                        // both pthread and `TIME_UTC` deadlines are on the realtime clock
+                        let d = self.duration(ctx, &func_name(), args[1], "libc::CLOCK_REALTIME");
+                        let (arg, guard) = arg(0);
+                        let new_e = format!(
+                            "{{
+        {0}_opt = timed_lock({2}, || {1}.{5}());
+        if {0}_opt.is_some() {{ {3} }} else {{ {4} }}
     }}",
-                            guard, arg, lock_method
+                            guard,
+                            arg,
+                            d,
+                            ok_code(code),
+                            ret_code(code, "ETIMEDOUT"),
+                            lock_method
                         );
                         add_replacement(ctx, e.span, new_e);
                         self.use_guard(func_name(), format!("{}_opt", guard));
@@ -923,25 +1810,43 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                     Some(
                         "pthread_mutex_unlock" | "pthread_spin_unlock" | "pthread_rwlock_unlock",
                     ) => {
+                        let path = expr_to_path(ctx, args[0]).unwrap();
                         let guard = arg(0).1;
                         self.use_guard(func_name(), guard.clone());
-                        let new_e = if in_assignment(ctx, e, false) {
-                            format!("{{ drop({}); 0 }}", guard)
+                        let new_e = if held_depth(&path) > 1 {
+                            // a relocked guard is kept until its last unlock
+                            if result_used(ctx, e) {
+                                format!("{{ {}.unlock(); {} }}", guard, ok_code(code))
+                            } else {
+                                format!("{}.unlock()", guard)
+                            }
+                        } else if result_used(ctx, e) {
+                            format!("{{ drop({}); {} }}", guard, ok_code(code))
                         } else {
                             format!("drop({})", guard)
                         };
                         add_replacement(ctx, e.span, new_e);
                     }
                     Some("pthread_cond_init") => {
-                        let new_e = if in_assignment(ctx, e, false) {
-                            format!("{{ {} = Condvar::new(); 0 }}", arg(0).0)
+                        let new_e =
+                            self.init_place(ctx, args[0], &arg(0).0, "Condvar::new()".into());
+                        let new_e = if result_used(ctx, e) {
+                            format!("{{ {}; {} }}", new_e, ok_code(code))
                         } else {
-                            format!("{} = Condvar::new()", arg(0).0)
+                            new_e
                         };
                         add_replacement(ctx, e.span, new_e);
                     }
-                    Some("pthread_cond_destroy") => {
-                        add_replacement(ctx, e.span, "0".to_string());
+                    Some(f @ ("pthread_cond_wait" | "pthread_cond_timedwait"))
+                        if self.waits_with_attributed_mutex(ctx, &func_name(), args[1]) =>
+                    {
+                        ctx.sess().span_warn(
+                            e.span,
+                            format!(
+                                "`{}` with a recursive or errorcheck mutex is not translated",
+                                f
+                            ),
+                        );
                     }
                     Some("pthread_cond_wait") => {
                         let c = arg(0).0;
@@ -950,48 +1855,271 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                         add_replacement(
                             ctx,
                             e.span,
-                            format!("{{ {1} = {0}.wait({1}).unwrap(); 0 }}", c, g),
+                            format!(
+                                "{{ {1} = {0}.wait({1}).unwrap(); {2} }}",
+                                c,
+                                g,
+                                ok_code(code)
+                            ),
                         );
                     }
                     Some("pthread_cond_timedwait") => {
                         let c = arg(0).0;
                         let g = arg(1).1;
-                        let t = arg(2).0;
                         self.use_guard(func_name(), g.clone());
-                        let f = func_name();
-                        let zero = "0".to_string();
-                        let tv_sec = self
-                            .duration_map
-                            .get(&(f.clone(), t.clone(), "tv_sec".to_string()))
-                            .unwrap_or(&zero);
-                        let tv_nsec = self
-                            .duration_map
-                            .get(&(f, t, "tv_nsec".to_string()))
-                            .unwrap_or(&zero);
+                        let clock = self.cond_clock(ctx, args[0]);
+                        let d = self.duration(ctx, &func_name(), args[2], &clock);
                         add_replacement(
                             ctx,
                             e.span,
                             format!(
                                 "{{
-        let {1}_tmp = {0}.wait_timeout({1}, Duration::new({2} as u64, {3} as u32)).unwrap();
+        let {1}_tmp = {0}.wait_timeout({1}, {2}).unwrap();
         {1} = {1}_tmp.0;
-        if {1}_tmp.1.timed_out() {{ libc::ETIMEDOUT }} else {{ 0 }}
+        if {1}_tmp.1.timed_out() {{ {3} }} else {{ {4} }}
     }}",
-                                c, g, tv_sec, tv_nsec
+                                c,
+                                g,
+                                d,
+                                ret_code(code, "ETIMEDOUT"),
+                                ok_code(code)
                             ),
                         );
                     }
+                    Some("sem_init") => {
+                        let v = span_to_string(ctx, args[2].span);
+                        let value = format!("Semaphore::new({} as libc::c_uint)", v);
+                        let new_e = if is_semaphore(args[0]) {
+                            self.init_place(ctx, args[0], &arg(0).0, value)
+                        } else {
+                            // the pointer may reach uninitialized memory
+                            let p = span_to_string(ctx, unwrap_cast_recursively(args[0]).span);
+                            format!("std::ptr::write({}, {})", p, value)
+                        };
+                        let new_e = if result_used(ctx, e) {
+                            format!("{{ {}; 0 }}", new_e)
+                        } else {
+                            new_e
+                        };
+                        add_replacement(ctx, e.span, new_e);
+                    }
+                    Some("sem_destroy") => {
+                        add_replacement(ctx, e.span, "0".to_string());
+                    }
+                    Some("sem_wait" | "sem_trywait" | "sem_post") => {
+                        let method = match f.as_deref().unwrap() {
+                            "sem_wait" => "wait",
+                            "sem_trywait" => "try_wait",
+                            _ => "post",
+                        };
+                        let sem = semaphore_arg(ctx, args[0]);
+                        add_replacement(ctx, e.span, format!("{}.{}()", sem, method));
+                    }
+                    Some("sem_timedwait") => {
+                        let d = self.duration(ctx, &func_name(), args[1], "libc::CLOCK_REALTIME");
+                        let sem = semaphore_arg(ctx, args[0]);
+                        add_replacement(ctx, e.span, format!("{}.timed_wait({})", sem, d));
+                    }
+                    Some("sem_getvalue") => {
+                        let v = span_to_string(ctx, args[1].span);
+                        let sem = semaphore_arg(ctx, args[0]);
+                        add_replacement(
+                            ctx,
+                            e.span,
+                            format!("{{ *({}) = {}.value(); 0 }}", v, sem),
+                        );
+                    }
+                    Some("pthread_create") => {
+                        let slot = unwrap_addr(unwrap_cast_recursively(args[0]));
+                        let func = func_name();
+                        if !self.is_thread_slot(ctx, &func, slot) {
+                            return;
+                        }
+                        let t = element_place(ctx, slot);
+                        // `thrd_create` takes no attributes
+                        let (f, a, ret) = if c11 {
+                            (args[1], args[2], "libc::c_int")
+                        } else {
+                            (args[2], args[3], "*mut libc::c_void")
+                        };
+                        let f = span_to_string(ctx, f.span);
+                        let a = span_to_string(ctx, a.span);
+                        let stack_size = expr_to_path(ctx, args[1])
+                            .filter(|_| !c11)
+                            .and_then(|attr| self.stack_size_map.get(&(func, attr.to_string())));
+                        let spawn = if let Some(size) = stack_size {
+                            format!(
+                                "{0} = std::thread::Builder::new().stack_size({1} as usize).spawn(move || thread_fn(thread_arg as *mut libc::c_void) as usize).ok();
+        if {0}.is_some() {{ 0 }} else {{ libc::EAGAIN }}",
+                                t, size
+                            )
+                        } else {
+                            format!(
+                                "{} = Some(std::thread::spawn(move || thread_fn(thread_arg as *mut libc::c_void) as usize));
+        {}",
+                                t,
+                                ok_code(code)
+                            )
+                        };
+                        let new_e = format!(
+                            "{{
+        let thread_fn: unsafe extern \"C\" fn(*mut libc::c_void) -> {} = {}.unwrap();
+        let thread_arg = {} as usize;
+        {}
+    }}",
+                            ret, f, a, spawn
+                        );
+                        add_replacement(ctx, e.span, new_e);
+                    }
+                    Some("pthread_join") => {
+                        let slot = unwrap_cast_recursively(args[0]);
+                        if !self.is_thread_slot(ctx, &func_name(), slot) {
+                            return;
+                        }
+                        let t = element_place(ctx, slot);
+                        let r = unwrap_cast_recursively(args[1]);
+                        let ok_branch = if span_to_string(ctx, r.span) == "0" {
+                            format!("Some(Ok(_)) => {},", ok_code(code))
+                        } else {
+                            let r = span_to_string(ctx, args[1].span);
+                            let ret = if c11 {
+                                "libc::c_int"
+                            } else {
+                                "*mut libc::c_void"
+                            };
+                            format!(
+                                "Some(Ok(v)) => {{ *({}) = v as {}; {} }}",
+                                r,
+                                ret,
+                                ok_code(code)
+                            )
+                        };
+                        let new_e = format!(
+                            "match {}.take().map(|h| h.join()) {{
+        {}
+        Some(Err(e)) => std::panic::resume_unwind(e),
+        None => {},
+    }}",
+                            t,
+                            ok_branch,
+                            ret_code(code, "ESRCH")
+                        );
+                        add_replacement(ctx, e.span, new_e);
+                    }
+                    Some("pthread_detach") => {
+                        let slot = unwrap_cast_recursively(args[0]);
+                        if !self.is_thread_slot(ctx, &func_name(), slot) {
+                            return;
+                        }
+                        let t = element_place(ctx, slot);
+                        let new_e = format!("{{ {}.take(); {} }}", t, ok_code(code));
+                        add_replacement(ctx, e.span, new_e);
+                    }
+                    Some("pthread_key_create") => {
+                        let key = some_or!(thread_key(ctx, args[0]), {
+                            ctx.sess().span_warn(
+                                e.span,
+                                "pthread key is not a global and is not translated",
+                            );
+                            return;
+                        });
+                        // keys not translated are reported by `check_crate`
+                        if !self.key_dtor_map.contains_key(&key) {
+                            return;
+                        }
+                        add_replacement(ctx, e.span, "0".to_string());
+                    }
+                    Some("pthread_key_delete") => {
+                        let key = some_or!(thread_key(ctx, args[0]), return);
+                        if !self.key_dtor_map.contains_key(&key) {
+                            return;
+                        }
+                        add_replacement(ctx, e.span, "0".to_string());
+                    }
+                    Some("pthread_getspecific") => {
+                        let key = some_or!(thread_key(ctx, args[0]), return);
+                        if !self.key_dtor_map.contains_key(&key) {
+                            return;
+                        }
+                        add_replacement(ctx, e.span, format!("{}.with(ThreadSpecific::get)", key));
+                    }
+                    Some("pthread_setspecific") => {
+                        let key = some_or!(thread_key(ctx, args[0]), return);
+                        if !self.key_dtor_map.contains_key(&key) {
+                            return;
+                        }
+                        let mut set = format!("{}.with(|k| k.set(value))", key);
+                        if result_used(ctx, e) {
+                            set.push_str("; 0");
+                        }
+                        bind_value(ctx, e, args[1], Some("*mut libc::c_void"), &set);
+                    }
+                    Some("pthread_once") => {
+                        let once = unwrap_addr(unwrap_cast_recursively(args[0]));
+                        match resolve_path(ctx, once) {
+                            Some(Res::Def(DefKind::Static(_), def_id))
+                                if !self
+                                    .kept_onces
+                                    .contains(&ctx.tcx.item_name(def_id).to_ident_string()) => {}
+                            _ => return,
+                        }
+                        let once = span_to_string(ctx, once.span);
+                        let f =
+                            span_to_string(ctx, unwrap_cast_recursively(unwrap_call(args[1])).span);
+                        let new_e = if result_used(ctx, e) {
+                            format!("{{ {}.call_once(|| {}()); 0 }}", once, f)
+                        } else {
+                            format!("{}.call_once(|| {}())", once, f)
+                        };
+                        add_replacement(ctx, e.span, new_e);
+                    }
+                    Some("pthread_barrier_init") => {
+                        let n = span_to_string(ctx, args[2].span);
+                        let value = format!("Some(Barrier::new(({}) as usize))", n);
+                        let init = self.init_place(ctx, args[0], &arg(0).0, value);
+                        let new_e = if result_used(ctx, e) {
+                            format!("{{ {}; 0 }}", init)
+                        } else {
+                            init
+                        };
+                        add_replacement(ctx, e.span, new_e);
+                    }
+                    Some("pthread_barrier_destroy") => {
+                        let new_e = if result_used(ctx, e) {
+                            format!("{{ {}.take(); 0 }}", arg(0).0)
+                        } else {
+                            format!("{}.take()", arg(0).0)
+                        };
+                        add_replacement(ctx, e.span, new_e);
+                    }
+                    Some("pthread_barrier_wait") => {
+                        let wait = format!(
+                            "{}.as_ref().expect(\"barrier is used before pthread_barrier_init\").wait()",
+                            arg(0).0
+                        );
+                        let hir = ctx.tcx.hir();
+                        let parent = hir.get(hir.get_parent_node(e.hir_id));
+                        let new_e = if let Node::Stmt(_) = parent {
+                            wait
+                        } else {
+                            format!(
+                                "(if {}.is_leader() {{ libc::PTHREAD_BARRIER_SERIAL_THREAD }} else {{ 0 }})",
+                                wait
+                            )
+                        };
+                        add_replacement(ctx, e.span, new_e);
+                    }
                     Some("pthread_cond_signal") => {
-                        let new_e = if in_assignment(ctx, e, false) {
-                            format!("{{ {}.notify_one(); 0 }}", arg(0).0)
+                        let new_e = if result_used(ctx, e) {
+                            format!("{{ {}.notify_one(); {} }}", arg(0).0, ok_code(code))
                         } else {
                             format!("{}.notify_one()", arg(0).0)
                         };
                         add_replacement(ctx, e.span, new_e);
                     }
                     Some("pthread_cond_broadcast") => {
-                        let new_e = if in_assignment(ctx, e, false) {
-                            format!("{{ {}.notify_all(); 0 }}", arg(0).0)
+                        let new_e = if result_used(ctx, e) {
+                            format!("{{ {}.notify_all(); {} }}", arg(0).0, ok_code(code))
                         } else {
                             format!("{}.notify_all()", arg(0).0)
                         };
@@ -1001,11 +2129,9 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                         if f == "main_0" {
                             return;
                         }
-                        let FunctionSummary {
-                            entry_mutex: entry,
-                            ret_mutex: ret,
-                            ..
-                        } = some_or!(function_mutex_map().get(f), return);
+                        let summary = some_or!(function_mutex_map().get(f), return);
+                        let entry = summary.entry_locks();
+                        let ret = summary.ret_locks();
                         let params = some_or!(self.params_map.get(f), return).clone();
                         // param-to-arg aliasing
                         let alias_mutex = |m: &ExprPath| {
@@ -1017,7 +2143,7 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                                 params.iter().enumerate().find(|(_, p)| &m.base == *p),
                                 return m
                             );
-                            let arg = some_or!(expr_to_path(ctx, &args[i]), return m);
+                            let arg = some_or!(expr_to_path(ctx, args[i]), return m);
                             m.set_base(&arg);
                             m
                         };
@@ -1082,7 +2208,7 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                 if f == "main_0" {
                     return;
                 }
-                let ret = &func_summary().ret_mutex;
+                let ret = func_summary().ret_locks();
                 if !ret.is_empty() {
                     let ret_vals = ret.iter().map(|m| m.guard()).collect();
                     self.use_guards(f, &ret_vals);
@@ -1131,15 +2257,30 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                     .collect();
                 for f in fs.iter() {
                     let name = f.ident.name.to_ident_string();
-                    let ftyp = if let Some(field_ty_map) = &field_ty_map_opt {
+                    let ftyp = normalize_type(if let Some(field_ty_map) = &field_ty_map_opt {
                         field_ty_map.get(&name).unwrap().clone()
                     } else {
                         type_to_string(type_of(ctx, f.expr.hir_id))
-                    };
+                    });
                     if ftyp.contains("pthread_cond_t") {
                         add_replacement(ctx, f.expr.span, "Condvar::new()".to_string());
                         continue;
                     }
+                    if ftyp == "sem_t" {
+                        add_replacement(ctx, f.expr.span, "Semaphore::new(0)".to_string());
+                        continue;
+                    }
+                    if ftyp == "pthread_barrier_t" {
+                        add_replacement(ctx, f.expr.span, "None".to_string());
+                        continue;
+                    }
+                    if self
+                        .thread_slots
+                        .contains(&ThreadSlot::Field(typ.clone(), name.clone()))
+                    {
+                        add_replacement(ctx, f.expr.span, "None".to_string());
+                        continue;
+                    }
                     if v.iter().any(|(x, _, _)| *x == name) {
                         let span = f.span.with_hi(f.span.hi() + BytePos(1));
                         add_replacement(ctx, span, "".to_string());
@@ -1152,7 +2293,7 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                         let lock_typ = if ftyp.contains("pthread_rwlock_t") {
                             "RwLock"
                         } else {
-                            "Mutex"
+                            lock_type_of(format!("{}.{}", typ, name), "Mutex")
                         };
                         let pfs: Vec<_> = v
                             .iter()
@@ -1175,6 +2316,20 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
             }
             // global variable
             ExprKind::Path(_) => {
+                if let Some((x, cell)) = self.thread_local_of(ctx, e) {
+                    let new_e = match temporary_access(ctx, e) {
+                        // assignments are rewritten as a whole
+                        Some(TemporaryAccess::Write) => return,
+                        Some(TemporaryAccess::Read) if cell == "Cell" => {
+                            format!("{}.with(std::cell::Cell::get)", x)
+                        }
+                        Some(TemporaryAccess::Read) => format!("{}.with(|cell| *cell.borrow())", x),
+                        // a borrow outlives the closure, so the cell is reached by its pointer
+                        None => format!("(*{}.with(std::cell::{}::as_ptr))", x, cell),
+                    };
+                    add_replacement(ctx, e.span, new_e);
+                    return;
+                }
                 if let Some(x) = name(e) {
                     if let Some(m) = global_mutex_map().get(&x) {
                         let mutex = ExprPath::new(m.clone(), vec![]);
@@ -1183,7 +2338,9 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                             self.use_guard(func_name(), guard.clone());
                             format!("(*{}).{}", guard, x)
                         } else {
-                            format!("{}.get_mut().unwrap().{}", m, x)
+                            let rwlock = self.is_rwlock_global(m);
+                            let safe = self.is_safe_global(m);
+                            unlocked_data(ctx, e, m, rwlock, safe, &x)
                         };
                         add_replacement(ctx, e.span, new_e);
                     }
@@ -1201,8 +2358,10 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                             self.use_guard(func_name(), guard.clone());
                             format!("(*{}).{}", guard, a)
                         } else {
-                            let i = span_to_string(ctx, i.span);
-                            format!("{}[{}].get_mut().unwrap().{}", m, i, a)
+                            let lock = format!("{}[{}]", m, span_to_string(ctx, i.span));
+                            let rwlock = self.is_rwlock_global(m);
+                            let safe = self.is_safe_global(m);
+                            unlocked_data(ctx, e, &lock, rwlock, safe, &a)
                         };
                         add_replacement(ctx, e.span, new_e);
                     }
@@ -1234,12 +2393,30 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                     if assigned && initialized {
                         return;
                     }
-                    let s = span_to_string(ctx, s.span);
-                    let new_e = format!("{}.{}.get_mut().unwrap().{}", s, m, f);
+                    let safe = safe_globals() && self.is_temporary(ctx, &func_name(), &mutex, e);
+                    if safe_globals() && !safe {
+                        ctx.sess().span_warn(
+                            e.span,
+                            format!(
+                                "data of `{}` is borrowed or may be accessed while it is held and is accessed by `get_mut`",
+                                mutex
+                            ),
+                        );
+                    }
+                    let rwlock = self
+                        .struct_def_map
+                        .get(&ty)
+                        .and_then(|fs| fs.get(m))
+                        .map_or(false, |t| t.contains("pthread_rwlock_t"));
+                    let lock = format!("{}.{}", span_to_string(ctx, s.span), m);
+                    let new_e = unlocked_data(ctx, e, &lock, rwlock, safe, &f);
                     add_replacement(ctx, e.span, new_e);
                 }
             }
-            ExprKind::Assign(lhs, _, _) => {
+            ExprKind::Assign(lhs, rhs, _) => {
+                if self.thread_local_assign(ctx, e, lhs, rhs, None) {
+                    return;
+                }
                 if let Some(mut path) = expr_to_path(ctx, lhs) {
                     while path.pop().is_some() {
                         let ty = self.get_type(&path, &func_name());
@@ -1285,23 +2462,49 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                     _ => (),
                 }
             }
+            ExprKind::AssignOp(op, lhs, rhs) => {
+                self.thread_local_assign(ctx, e, lhs, rhs, Some(op.node));
+            }
             ExprKind::If(c, t, f) => {
-                let (expr, eq) = some_or!(read_condition(ctx, c), return);
-                let line = span_lines(ctx, c.span)
-                    .drain_filter(|_| true)
-                    .min()
-                    .unwrap();
-                let g = some_or!(self.trylock_map.get(&(func_name(), expr, line)), return);
                 let span = c
                     .span
                     .with_lo(c.span.lo() - BytePos(3))
                     .with_hi(c.span.hi() + BytePos(2));
+                if let ExprKind::Binary(op, _, _) = unwrap_drop_temps(c).kind {
+                    if matches!(op.node, BinOpKind::And | BinOpKind::Or) {
+                        self.chained_trylocks(ctx, func_name(), op.node, *c, *t, *f);
+                        return;
+                    }
+                }
+                let (expr, eq) = some_or!(read_condition(ctx, *c), return);
+                let (scrutinee, g) = if let Some((scrutinee, g)) =
+                    trylock_call(ctx, expr, &self.write_rwlocks)
+                {
+                    // the trylock is lowered together with the condition
+                    self.replaced.insert(c.span);
+                    self.use_guard(func_name(), g.clone());
+                    if eq {
+                        let new_e =
+                            format!("if let Some({0}_tmp) = {1} {{ {0} = {0}_tmp;", g, scrutinee);
+                        add_replacement(ctx, span, new_e);
+                        return;
+                    }
+                    (scrutinee, g)
+                } else {
+                    let expr = span_to_string(ctx, expr.span);
+                    let line = span_lines(ctx, c.span)
+                        .drain_filter(|_| true)
+                        .min()
+                        .unwrap();
+                    let g = some_or!(self.trylock_map.get(&(func_name(), expr, line)), return);
+                    (format!("{}_opt", g), g.clone())
+                };
                 let true_branch = if eq {
                     format!("Some({0}_tmp) => {{ {0} = {0}_tmp;", g)
                 } else {
                     "None => {".to_string()
                 };
-                add_replacement(ctx, span, format!("match {}_opt {{ {}", g, true_branch));
+                add_replacement(ctx, span, format!("match {} {{ {}", scrutinee, true_branch));
                 if let Some(f) = f {
                     let span = t
                         .span
@@ -1326,6 +2529,53 @@ pub static mut {2}: [{3}<{0}>; {4}] = [{5}
                     add_replacement(ctx, span, false_branch);
                 }
             }
+            ExprKind::Loop(b, _, LoopSource::While, _) => {
+                // `while` loops are desugared into `loop { if c { .. } else { break } }`
+                let (c, t) = match b.expr.map(|b| &b.kind) {
+                    Some(ExprKind::If(c, t, _)) => (c, t),
+                    _ => return,
+                };
+                let trylock = read_condition(ctx, *c).and_then(|(expr, eq)| {
+                    Some((trylock_call(ctx, expr, &self.write_rwlocks)?, eq))
+                });
+                let ((scrutinee, g), eq) = some_or!(trylock, {
+                    self.wait_loop(ctx, func_name(), e, *c, *t, is_protected);
+                    return;
+                });
+                self.replaced.insert(c.span);
+                self.use_guard(func_name(), g.clone());
+                let (true_branch, false_branch) = if eq {
+                    (
+                        format!("Some({0}_tmp) => {{ {0} = {0}_tmp;", g),
+                        "None => break,".to_string(),
+                    )
+                } else {
+                    (
+                        "None => {".to_string(),
+                        format!("Some({0}_tmp) => {{ {0} = {0}_tmp; break; }}", g),
+                    )
+                };
+                let span = e.span.with_hi(t.span.lo() + BytePos(1));
+                let new_e = format!("loop {{ match {} {{ {}", scrutinee, true_branch);
+                add_replacement(ctx, span, new_e);
+                let span = t.span.shrink_to_hi();
+                add_replacement(ctx, span, format!(" {} }} }}", false_branch));
+            }
+            ExprKind::Match(scrutinee, arms, MatchSource::Normal) => {
+                // the call is lowered by itself; the guard moves into the arm of success
+                let (_, g) = some_or!(trylock_call(ctx, scrutinee, &self.write_rwlocks), return);
+                for arm in arms.iter() {
+                    if span_to_string(ctx, arm.pat.span) != "0" {
+                        continue;
+                    }
+                    if let ExprKind::Block(_, _) = arm.body.kind {
+                        let pos = arm.body.span.lo() + BytePos(1);
+                        let span = arm.body.span.with_lo(pos).with_hi(pos);
+                        add_replacement(ctx, span, format!(" {0} = {0}_opt.unwrap();", g));
+                        self.use_guard(func_name(), g.clone());
+                    }
+                }
+            }
             ExprKind::MethodCall(method, args, _) => {
                 // ((*a).b[(*a).c as usize]).as_mut_ptr()
                 // ==>
@@ -1401,61 +2651,1144 @@ impl RewritePass {
         }
     }
 
-    fn struct_of_path(&self, func: &String, s: &ExprPath) -> String {
-        if s.is_variable() {
-            struct_of(&s.base)
-        } else {
-            let mut s = s.clone();
-            let f = s.pop().unwrap();
-            let ty = self.get_type(&s, func);
-            struct_of2(ty, f.inner())
+    /// Whether the deadline `t` is only advanced by `tv_sec += x` or `tv_nsec += x` in `func`, so
+    /// that the offsets give the timeout.
+    fn is_offset_deadline(&self, func: &String, t: &String) -> bool {
+        ["tv_sec", "tv_nsec"].iter().any(|f| {
+            self.duration_map
+                .contains_key(&(func.clone(), t.clone(), f.to_string()))
+        })
+    }
+
+    /// Collapses `while c { pthread_cond_wait(..); }` into `Condvar::wait_while`, whose closure
+    /// shadows the guard so that accesses in `c` go through the closure argument; `c` must read
+    /// data protected by the mutex waited on. A timed wait is collapsed only if the loop breaks
+    /// on `ETIMEDOUT`, as it otherwise keeps waiting after the deadline.
+    fn wait_loop<'tcx, F: Fn(&ExprPath) -> bool>(
+        &mut self,
+        ctx: &LateContext<'tcx>,
+        func: String,
+        e: &'tcx Expr<'tcx>,
+        c: &'tcx Expr<'tcx>,
+        t: &'tcx Expr<'tcx>,
+        is_protected: F,
+    ) {
+        let block = match t.kind {
+            ExprKind::Block(b, _) => b,
+            _ => return,
+        };
+        let exprs: Vec<_> = block
+            .stmts
+            .iter()
+            .filter_map(|stmt| match stmt.kind {
+                StmtKind::Semi(e) | StmtKind::Expr(e) => Some(e),
+                _ => None,
+            })
+            .chain(block.expr)
+            .collect();
+        if exprs.len() != block.stmts.len() + block.expr.iter().count() {
+            return;
         }
+        // `wait(..);`, `if wait(..) == ETIMEDOUT { break; }` or
+        // `rc = wait(..); if rc == ETIMEDOUT { break; }`
+        let (call, rc, timeout) = match exprs[..] {
+            [call] => match timeout_break(call) {
+                Some((call, v)) => (call, None, Some(v)),
+                None => (call, None, None),
+            },
+            [assign, check] => {
+                let (rc, call) = match assign.kind {
+                    ExprKind::Assign(rc, call, _) => (rc, call),
+                    _ => return,
+                };
+                let (x, v) = some_or!(timeout_break(check), return);
+                if span_to_string(ctx, x.span) != span_to_string(ctx, rc.span) {
+                    return;
+                }
+                (call, Some(rc), Some(v))
+            }
+            _ => return,
+        };
+        let (f, args) = match call.kind {
+            ExprKind::Call(f, args) => (f, call_args(f, args)),
+            _ => return,
+        };
+        let timed = match api_name(f).as_deref() {
+            Some("pthread_cond_wait") => false,
+            Some("pthread_cond_timedwait") => true,
+            _ => return,
+        };
+        let code = return_code(f);
+        let checked = timeout.map_or(false, |v| is_timeout_code(ctx, v, code));
+        if timed != checked || timed != timeout.is_some() {
+            return;
+        }
+        let mutex = some_or!(expr_to_path(ctx, args[1]), return);
+        if !is_protected(&mutex)
+            || !reads_protected(ctx, c, &mutex)
+            || self.waits_with_attributed_mutex(ctx, &func, args[1])
+        {
+            return;
+        }
+        let (cond, _) = normalize_arg(ctx, args[0]);
+        let (_, g) = normalize_arg(ctx, args[1]);
+        self.replaced.insert(t.span);
+        self.use_guard(func.clone(), g.clone());
+        let (head, tail) = if timed {
+            let clock = self.cond_clock(ctx, args[0]);
+            let d = self.duration(ctx, &func, args[2], &clock);
+            match rc {
+                Some(rc) => (
+                    format!(
+                        "{{ let ({0}_tmp, {0}_res) = {1}.wait_timeout_while({0}, {2}, |{0}| ",
+                        g, cond, d
+                    ),
+                    format!(
+                        ").unwrap(); {0} = {0}_tmp; {1} = if {0}_res.timed_out() {{ {2} }} else {{ {3} }}; }}",
+                        g,
+                        span_to_string(ctx, rc.span),
+                        ret_code(code, "ETIMEDOUT"),
+                        ok_code(code)
+                    ),
+                ),
+                None => (
+                    format!("{0} = {1}.wait_timeout_while({0}, {2}, |{0}| ", g, cond, d),
+                    ").unwrap().0;".to_string(),
+                ),
+            }
+        } else {
+            (
+                format!("{0} = {1}.wait_while({0}, |{0}| ", g, cond),
+                ").unwrap();".to_string(),
+            )
+        };
+        add_replacement(ctx, e.span.with_hi(c.span.lo()), head);
+        add_replacement(ctx, e.span.with_lo(c.span.hi()), tail);
     }
-}
 
-// suggestions for left-hand side of expressions
-fn add_replacement(ctx: &LateContext<'_>, span: Span, replacement: String) {
-    use rustfix::{LinePosition, LineRange};
+    /// Checks if the lock global `m` is emitted as an immutable `static`; locks whose data is
+    /// not `Send` stay `static mut`, as it may alias memory reached without the lock, and so do
+    /// locks whose data cannot be reached by temporary guards outside critical sections.
+    fn is_safe_global(&self, m: &str) -> bool {
+        safe_globals()
+            && !self.unsafe_globals.contains(m)
+            && self
+                .pointer_classes(&self.global_data_fields(m))
+                .iter()
+                .all(|(_, c)| *c != PointerClass::Shared)
+    }
 
-    let source_map = ctx.sess().source_map();
-    let fname = source_map.span_to_filename(span);
+    fn is_rwlock_global(&self, m: &str) -> bool {
+        self.global_def_map
+            .get(m)
+            .map_or(false, |(t, _)| t.contains("pthread_rwlock_t"))
+    }
 
-    let file = source_map.get_source_file(&fname).unwrap();
-    // get the line and the column numbers
-    let lo = file.lookup_file_pos_with_col_display(span.lo());
-    let hi = file.lookup_file_pos_with_col_display(span.hi());
-    let line_range = LineRange {
-        start: LinePosition {
-            line: lo.0,
-            column: lo.2,
-        },
-        end: LinePosition {
-            line: hi.0,
-            column: hi.2,
-        },
-    };
-    // get the char offsets within the file
-    let lo_offset = file.original_relative_byte_pos(span.lo()).0;
-    let hi_offset = file.original_relative_byte_pos(span.hi()).0;
-    let snippet = Snippet {
-        file_name: fname.prefer_remapped().to_string(),
-        line_range,
-        range: (lo_offset as usize)..(hi_offset as usize),
-        text: (
-            "".into(),
-            source_map.span_to_snippet(span).unwrap(),
-            "".into(),
-        ),
-    };
+    /// Checks if the data of the lock at `mutex` accessed by `e` in `func` outside critical
+    /// sections can be reached by a temporary guard: the access is not a borrow, and the lock is
+    /// not held by the current thread, which would deadlock.
+    fn is_temporary(
+        &self,
+        ctx: &LateContext<'_>,
+        func: &String,
+        mutex: &ExprPath,
+        e: &Expr<'_>,
+    ) -> bool {
+        let lines = span_lines(ctx, e.span);
+        temporary_access(ctx, e).is_some()
+            && !self.may_hold(func, mutex, &lines, &mut BTreeSet::new())
+    }
 
-    REPLACEMENTS.lock().unwrap().push(Replacement {
-        snippet,
-        replacement,
-    });
-}
+    /// Checks if the lock at `m` may be held by the current thread at `lines` of `func`, where
+    /// callers may hold it as well; locks are told apart only by name, as paths may alias.
+    fn may_hold(
+        &self,
+        func: &String,
+        m: &ExprPath,
+        lines: &BTreeSet<usize>,
+        visited: &mut BTreeSet<(String, BTreeSet<usize>)>,
+    ) -> bool {
+        if !visited.insert((func.clone(), lines.clone())) {
+            return false;
+        }
+        let summary = some_or!(function_mutex_map().get(func), return true);
+        let may_line = some_or!(summary.may_line.as_ref(), return true);
+        let maps = [
+            &summary.mutex_line,
+            &summary.rdlock_line,
+            &summary.wrlock_line,
+            may_line,
+        ];
+        let held = maps
+            .iter()
+            .flat_map(|map| map.iter())
+            .any(|(h, ls)| same_lock(h, m) && lines.iter().any(|l| ls.contains(l)));
+        held || summary.entry_locks().iter().any(|h| same_lock(h, m))
+            || self
+                .call_sites
+                .iter()
+                .filter(|c| &c.1 == func)
+                .any(|(caller, _, _, lines)| self.may_hold(caller, m, lines, visited))
+    }
 
-fn hid_to_string(ctx: &LateContext<'_>, hid: HirId) -> String {
-    span_to_string(ctx, ctx.tcx.hir().span(hid))
+    /// Checks if `func` may be called with the lock at `m` held, so that locking it relocks it.
+    fn may_relock(&self, func: &String, m: &ExprPath) -> bool {
+        self.call_sites
+            .iter()
+            .filter(|c| &c.1 == func)
+            .any(|(caller, _, _, lines)| self.may_hold(caller, m, lines, &mut BTreeSet::new()))
+    }
+
+    /// `unsafe impl Send` for the data struct `s`, and `Sync` for rwlocks, if its fields
+    /// `(key, type)` hold raw pointers, which are neither; each pointer is justified by its class,
+    /// and no impl is emitted if a pointer may alias memory reached without the lock.
+    fn send_impls(
+        &self,
+        ctx: &LateContext<'_>,
+        span: Span,
+        s: &str,
+        fs: Vec<(String, &String)>,
+        rwlock: bool,
+    ) -> String {
+        let classes = self.pointer_classes(&fs);
+        if classes.is_empty() {
+            return String::new();
+        }
+        if let Some((x, _)) = classes.iter().find(|(_, c)| *c == PointerClass::Shared) {
+            ctx.sess().span_warn(
+                span,
+                format!(
+                    "`{}` may alias memory reached without the lock; `{}` is not `Send`",
+                    x, s
+                ),
+            );
+            return String::new();
+        }
+        let reasons: Vec<_> = classes
+            .iter()
+            .filter_map(|(x, c)| Some(format!("\n// `{}` {}", x, c.justification()?)))
+            .collect();
+        let mut code = reasons.concat();
+        code.push_str(&format!("\nunsafe impl Send for {} {{}}", s));
+        if rwlock {
+            code.push_str(&format!("\nunsafe impl Sync for {} {{}}", s));
+        }
+        code
+    }
+
+    /// The classes of the raw pointers held by the data fields `(key, type)` by their names;
+    /// pointers in nested structs are not classified and may alias.
+    fn pointer_classes<'a>(&self, fs: &'a [(String, &String)]) -> Vec<(&'a str, PointerClass)> {
+        fs.iter()
+            .filter(|(_, t)| self.holds_raw_pointer(t))
+            .map(|(key, t)| {
+                let x = key.rsplit('.').next().unwrap();
+                let class = if is_raw_pointer(t) {
+                    self.pointer_class_map
+                        .get(key)
+                        .copied()
+                        .unwrap_or(PointerClass::Shared)
+                } else {
+                    PointerClass::Shared
+                };
+                (x, class)
+            })
+            .collect()
+    }
+
+    /// The data fields `(name, type)` of the lock global `m`, or of the array of locks `m`.
+    fn global_data_fields(&self, m: &str) -> Vec<(String, &String)> {
+        let globals = global_mutex_map()
+            .iter()
+            .filter(|(_, n)| *n == m)
+            .filter_map(|(x, _)| Some((x.clone(), &self.global_def_map.get(x)?.0)));
+        let arrays = array_mutex_map()
+            .iter()
+            .filter(|(_, n)| *n == m)
+            .filter_map(|(x, _)| Some((x.clone(), &self.array_def_map.get(x)?.0)));
+        globals.chain(arrays).collect()
+    }
+
+    /// Checks if values of type `t` hold raw pointers, directly or in nested structs.
+    fn holds_raw_pointer(&self, t: &str) -> bool {
+        is_raw_pointer(t)
+            || self
+                .trans_struct_def_map
+                .get(t)
+                .map_or(false, |ts| ts.iter().any(|t| is_raw_pointer(t)))
+    }
+
+    /// Checks if the struct `typ` contains locks or other synchronization primitives, directly or
+    /// in nested structs.
+    fn contains_lock(&self, typ: &str) -> bool {
+        self.trans_struct_def_map
+            .get(typ)
+            .map_or(false, |ts| ts.iter().any(|t| is_sync_type(t)))
+    }
+
+    /// The first array field of the struct `typ`, directly or in nested lock-bearing structs,
+    /// whose elements are synchronization primitives, lock-bearing structs or thread slots, which
+    /// cannot be made from zero bytes.
+    fn unzeroable_array(&self, typ: &str) -> Option<String> {
+        let fields = self.struct_def_map.get(typ)?;
+        fields.iter().find_map(|(x, t)| match array_element(t) {
+            Some(elem) => {
+                let slot = ThreadSlot::Field(typ.to_string(), x.clone());
+                (is_sync_type(elem)
+                    || self.contains_lock(elem)
+                    || self.thread_slots.contains(&slot))
+                .then(|| format!("{}.{}", typ, x))
+            }
+            None if self.contains_lock(t) => self.unzeroable_array(t),
+            None => None,
+        })
+    }
+
+    /// A value of the struct `typ` with fresh synchronization primitives, whose other fields at
+    /// `prefix` are given by `field` from their paths, types and accesses.
+    fn fresh_struct(
+        &self,
+        typ: &str,
+        prefix: &str,
+        field: &dyn Fn(&str, &str, FieldAccess<'_>) -> String,
+    ) -> String {
+        let empty = BTreeMap::new();
+        let fields = self.struct_def_map.get(typ).unwrap_or(&empty);
+        let map = struct_mutex_map().get(typ).unwrap_or(&empty);
+        let path = |x: &str| {
+            if prefix.is_empty() {
+                x.to_string()
+            } else {
+                format!("{}.{}", prefix, x)
+            }
+        };
+        let inits = fields
+            .iter()
+            .filter(|(x, _)| !map.contains_key(*x))
+            .map(|(x, t)| {
+                let slot = ThreadSlot::Field(typ.to_string(), x.clone());
+                let v = if t == "pthread_cond_t" {
+                    "Condvar::new()".to_string()
+                } else if t == "sem_t" {
+                    "Semaphore::new(0)".to_string()
+                } else if t == "pthread_barrier_t" || self.thread_slots.contains(&slot) {
+                    "None".to_string()
+                } else if t == "pthread_mutex_t"
+                    || t == "pthread_spinlock_t"
+                    || t == "pthread_rwlock_t"
+                {
+                    let lock_ty = if t != "pthread_rwlock_t" {
+                        lock_type_of(format!("{}.{}", typ, x), "Mutex")
+                    } else {
+                        "RwLock"
+                    };
+                    let lock = path(x);
+                    let data = map
+                        .iter()
+                        .filter(|(_, m)| *m == x)
+                        .map(|(y, _)| {
+                            let access = FieldAccess::Locked(&lock, lock_ty);
+                            let v = field(&path(y), &fields[y], access);
+                            format!("{}: {}", y, v)
+                        })
+                        .collect();
+                    let data = join(data, ", ");
+                    format!("{}::new({} {{ {} }})", lock_ty, struct_of2(typ, x), data)
+                } else if self.contains_lock(t) {
+                    self.fresh_struct(t, &path(x), field)
+                } else if let Some(ty) = atomic_map().get(&format!("{}.{}", typ, x)) {
+                    atomic_new(ty, field(&path(x), t, FieldAccess::Atomic))
+                } else {
+                    field(&path(x), t, FieldAccess::Plain)
+                };
+                format!("{}: {}", x, v)
+            })
+            .collect();
+        format!("{} {{ {} }}", typ, join(inits, ", "))
+    }
+
+    /// A copy of the struct `typ` at `src` with fresh synchronization primitives, reading the
+    /// protected data through the guards held or through temporary guards.
+    fn copy_struct<'tcx, F: Fn(&ExprPath) -> bool>(
+        &mut self,
+        ctx: &LateContext<'tcx>,
+        func: &str,
+        typ: &str,
+        src: &'tcx Expr<'tcx>,
+        is_protected: &F,
+    ) -> String {
+        let place = span_to_string(ctx, src.span);
+        let place = if type_of(ctx, src.hir_id).is_unsafe_ptr() {
+            format!("(*{})", place)
+        } else {
+            place
+        };
+        let mut guards = BTreeMap::new();
+        if let (Some(path), Some(map)) = (expr_to_path(ctx, src), struct_mutex_map().get(typ)) {
+            for m in map.values() {
+                let mut mutex = path.clone();
+                mutex.add_suffix(ExprPathProj::Field(m.clone()));
+                if is_protected(&mutex) {
+                    guards.insert(m.clone(), mutex.guard());
+                }
+            }
+        }
+        for g in guards.values() {
+            self.use_guard(func.to_string(), g.clone());
+        }
+        self.fresh_struct(typ, "", &|x, _, access| match access {
+            FieldAccess::Plain => format!("{}.{}", place, x),
+            FieldAccess::Atomic => {
+                format!("{}.{}.load(std::sync::atomic::Ordering::SeqCst)", place, x)
+            }
+            FieldAccess::Locked(m, lock_ty) => {
+                let f = x.rsplit('.').next().unwrap();
+                match guards.get(m) {
+                    Some(g) => format!("(*{}).{}", g, f),
+                    None => locked_read(&format!("{}.{}", place, m), lock_ty, f),
+                }
+            }
+        })
+    }
+
+    /// Rewrites a lock-bearing struct read by value, which is no longer `Copy`, into a copy with
+    /// fresh synchronization primitives; a copy assigned to a place initializes the place.
+    fn copy_value<'tcx, F: Fn(&ExprPath) -> bool>(
+        &mut self,
+        ctx: &LateContext<'tcx>,
+        func: &str,
+        e: &'tcx Expr<'tcx>,
+        is_protected: &F,
+    ) -> bool {
+        if let ExprKind::Assign(lhs, rhs, _) = &e.kind {
+            let ty = some_or!(self.lock_bearing_place(ctx, rhs), return false);
+            let value = self.copy_struct(ctx, func, &ty, rhs, is_protected);
+            let new_e = self.init_place(ctx, lhs, &span_to_string(ctx, lhs.span), value);
+            add_replacement(ctx, e.span, new_e);
+            self.replaced.insert(e.span);
+            return true;
+        }
+        let ty = some_or!(self.lock_bearing_place(ctx, e), return false);
+        let hir = ctx.tcx.hir();
+        let by_value = match hir.find(hir.get_parent_node(e.hir_id)) {
+            Some(Node::Local(l)) => l.init.map_or(false, |i| i.hir_id == e.hir_id),
+            Some(Node::ExprField(_)) => true,
+            Some(Node::Expr(p)) => match &p.kind {
+                ExprKind::Call(_, args) => args.iter().any(|a| a.hir_id == e.hir_id),
+                ExprKind::Ret(_) => true,
+                _ => false,
+            },
+            _ => false,
+        };
+        if !by_value {
+            return false;
+        }
+        let new_e = self.copy_struct(ctx, func, &ty, e, is_protected);
+        add_replacement(ctx, e.span, new_e);
+        self.replaced.insert(e.span);
+        true
+    }
+
+    /// The type of `e` if it is a place of a lock-bearing struct.
+    fn lock_bearing_place(&self, ctx: &LateContext<'_>, e: &Expr<'_>) -> Option<String> {
+        match &e.kind {
+            ExprKind::Path(_) => {
+                if !matches!(
+                    resolve_path(ctx, e),
+                    Some(Res::Local(_) | Res::Def(DefKind::Static(_), _))
+                ) {
+                    return None;
+                }
+            }
+            ExprKind::Field(_, _) | ExprKind::Unary(UnOp::Deref, _) | ExprKind::Index(_, _) => (),
+            _ => return None,
+        }
+        let ty = type_to_string(type_of(ctx, e.hir_id));
+        self.contains_lock(&ty).then(|| ty)
+    }
+
+    /// Moves the guards of trylocks tested in a `&&` or `||` chain into the branch where the
+    /// locks are held: the then branch for tests of success joined by `&&` and the else branch
+    /// for tests of failure joined by `||`. The other branch is also taken when a later test
+    /// fails after earlier trylocks succeeded, so it releases the locks acquired.
+    fn chained_trylocks<'tcx>(
+        &mut self,
+        ctx: &LateContext<'tcx>,
+        func: String,
+        op: BinOpKind,
+        c: &'tcx Expr<'tcx>,
+        t: &'tcx Expr<'tcx>,
+        f: Option<&'tcx Expr<'tcx>>,
+    ) {
+        let mut operands = vec![];
+        chain_operands(c, op, &mut operands);
+        let mut guards = String::new();
+        let mut releases = String::new();
+        for operand in operands {
+            let (call, eq) = some_or!(read_condition(ctx, operand), continue);
+            if eq != (op == BinOpKind::And) {
+                continue;
+            }
+            let (_, g) = some_or!(trylock_call(ctx, call, &self.write_rwlocks), continue);
+            guards.push_str(&format!(" {0} = {0}_opt.unwrap();", g));
+            releases.push_str(&format!(" {}_opt = None;", g));
+            self.use_guard(func.clone(), g);
+        }
+        if guards.is_empty() {
+            return;
+        }
+        let (held, failed) = if op == BinOpKind::And {
+            (Some(t), f)
+        } else {
+            (f, Some(t))
+        };
+        match held {
+            Some(b) if matches!(b.kind, ExprKind::Block(_, _)) => {
+                let pos = b.span.lo() + BytePos(1);
+                add_replacement(ctx, b.span.with_lo(pos).with_hi(pos), guards);
+            }
+            // `else if`
+            Some(_) => (),
+            None => add_replacement(ctx, t.span.shrink_to_hi(), format!(" else {{{} }}", guards)),
+        }
+        match failed {
+            Some(b) if matches!(b.kind, ExprKind::Block(_, _)) => {
+                let pos = b.span.lo() + BytePos(1);
+                add_replacement(ctx, b.span.with_lo(pos).with_hi(pos), releases);
+            }
+            // `else if`, which becomes the tail of a block
+            Some(b) => {
+                add_replacement(ctx, b.span.shrink_to_lo(), format!("{{{} ", releases));
+                add_replacement(ctx, b.span.shrink_to_hi(), " }".to_string());
+            }
+            None => add_replacement(
+                ctx,
+                t.span.shrink_to_hi(),
+                format!(" else {{{} }}", releases),
+            ),
+        }
+    }
+
+    /// The clock of the condition variable `c`, configured with `pthread_condattr_setclock`.
+    fn cond_clock(&self, ctx: &LateContext<'_>, c: &Expr<'_>) -> String {
+        lock_key(ctx, c)
+            .and_then(|k| self.cond_clock_map.get(&k))
+            .map_or("libc::CLOCK_REALTIME".to_string(), |c| {
+                format!("{} as libc::clockid_t", c)
+            })
+    }
+
+    /// The timeout until the absolute deadline `e` measured on `clock`.
+    fn duration(&self, ctx: &LateContext<'_>, func: &String, e: &Expr<'_>, clock: &str) -> String {
+        let t = deadline_name(ctx, e);
+        if !self.is_offset_deadline(func, &t) {
+            let e = unwrap_cast_recursively(e);
+            let deadline = match &e.kind {
+                ExprKind::AddrOf(_, _, e) => span_to_string(ctx, e.span),
+                _ => format!("(*{})", span_to_string(ctx, e.span)),
+            };
+            return format!(
+                "deadline_timeout({0}, {1}.tv_sec as i64, {1}.tv_nsec as i64)",
+                clock, deadline
+            );
+        }
+        let zero = "0".to_string();
+        let tv_sec = self
+            .duration_map
+            .get(&(func.clone(), t.clone(), "tv_sec".to_string()))
+            .unwrap_or(&zero);
+        let tv_nsec = self
+            .duration_map
+            .get(&(func.clone(), t, "tv_nsec".to_string()))
+            .unwrap_or(&zero);
+        format!("Duration::new({} as u64, {} as u32)", tv_sec, tv_nsec)
+    }
+
+    /// The key of the lock `m` in `func`, as in the maps of the analysis.
+    fn lock_key_of(&self, func: &String, m: &ExprPath) -> String {
+        if m.is_variable() {
+            m.base.clone()
+        } else {
+            let mut m = m.clone();
+            let f = m.pop().unwrap();
+            format!("{}.{}", self.get_type(&m, func), f.inner())
+        }
+    }
+
+    fn mutex_kind(&self, func: &String, m: &ExprPath) -> Option<MutexKind> {
+        mutex_kind_map().get(&self.lock_key_of(func, m)).copied()
+    }
+
+    /// Checks if `m` is a recursive or errorcheck mutex, whose lock may fail or relock it.
+    fn is_attributed(&self, func: &String, m: &ExprPath) -> bool {
+        matches!(
+            self.mutex_kind(func, m),
+            Some(MutexKind::Recursive | MutexKind::ErrorCheck)
+        )
+    }
+
+    fn is_write_rwlock(&self, func: &String, m: &ExprPath) -> bool {
+        self.write_rwlocks.contains(&self.lock_key_of(func, m))
+    }
+
+    fn is_write_rwlock_arg(&self, ctx: &LateContext<'_>, func: &String, m: &Expr<'_>) -> bool {
+        expr_to_path(ctx, m).map_or(false, |m| self.is_write_rwlock(func, &m))
+    }
+
+    /// Checks if a condition variable wait is with the mutex `m` of a kind set by attributes,
+    /// whose guards are not `MutexGuard`s and cannot be passed to `Condvar`.
+    fn waits_with_attributed_mutex(
+        &self,
+        ctx: &LateContext<'_>,
+        func: &String,
+        m: &Expr<'_>,
+    ) -> bool {
+        expr_to_path(ctx, m).map_or(false, |m| self.mutex_kind(func, &m).is_some())
+    }
+
+    fn guard_type(&self, func: &String, m: &ExprPath) -> &'static str {
+        self.mutex_kind(func, m)
+            .map_or("MutexGuard", |k| k.guard_type())
+    }
+
+    /// The modes (`true` for write) in which callers hold the rwlock `m` on entry to `func`.
+    fn entry_rwlock_modes(
+        &self,
+        func: &String,
+        m: &ExprPath,
+        visited: &mut BTreeSet<(String, ExprPath)>,
+    ) -> BTreeSet<bool> {
+        let mut modes = BTreeSet::new();
+        if !visited.insert((func.clone(), m.clone())) {
+            return modes;
+        }
+        let params = some_or!(self.params_map.get(func), return modes);
+        for (caller, _, args, lines) in self.call_sites.iter().filter(|c| &c.1 == func) {
+            // param-to-arg aliasing
+            let mut m = m.clone();
+            if !m.is_variable() {
+                if let Some(i) = params.iter().position(|p| p == &m.base) {
+                    let arg = some_or!(args.get(i).cloned().flatten(), continue);
+                    m.set_base(&arg);
+                }
+            }
+            let summary = some_or!(function_mutex_map().get(caller), continue);
+            if summary.entry_rwlock.contains(&m) {
+                modes.append(&mut self.entry_rwlock_modes(caller, &m, visited));
+                continue;
+            }
+            let held = |map: &BTreeMap<ExprPath, BTreeSet<usize>>| {
+                map.get(&m)
+                    .map_or(false, |ls| lines.iter().any(|l| ls.contains(l)))
+            };
+            if held(&summary.wrlock_line) {
+                modes.insert(true);
+            }
+            if held(&summary.rdlock_line) {
+                modes.insert(false);
+            }
+        }
+        modes
+    }
+
+    fn struct_of_path(&self, func: &String, s: &ExprPath) -> String {
+        if s.is_variable() {
+            struct_of(&s.base)
+        } else {
+            let mut s = s.clone();
+            let f = s.pop().unwrap();
+            let ty = self.get_type(&s, func);
+            struct_of2(ty, f.inner())
+        }
+    }
+}
+
+// suggestions for left-hand side of expressions
+fn add_replacement(ctx: &LateContext<'_>, span: Span, replacement: String) {
+    use rustfix::{LinePosition, LineRange};
+
+    let source_map = ctx.sess().source_map();
+    let fname = source_map.span_to_filename(span);
+
+    let file = source_map.get_source_file(&fname).unwrap();
+    // get the line and the column numbers
+    let lo = file.lookup_file_pos_with_col_display(span.lo());
+    let hi = file.lookup_file_pos_with_col_display(span.hi());
+    let line_range = LineRange {
+        start: LinePosition {
+            line: lo.0,
+            column: lo.2,
+        },
+        end: LinePosition {
+            line: hi.0,
+            column: hi.2,
+        },
+    };
+    // get the char offsets within the file
+    let lo_offset = file.original_relative_byte_pos(span.lo()).0;
+    let hi_offset = file.original_relative_byte_pos(span.hi()).0;
+    let snippet = Snippet {
+        file_name: fname.prefer_remapped().to_string(),
+        line_range,
+        range: (lo_offset as usize)..(hi_offset as usize),
+        text: (
+            "".into(),
+            source_map.span_to_snippet(span).unwrap(),
+            "".into(),
+        ),
+    };
+
+    REPLACEMENTS.lock().unwrap().push(Replacement {
+        snippet,
+        replacement,
+    });
+}
+
+/// Lock type for mutexes configured as recursive or error-checking. The mutex counts how many
+/// times its owner locked it, and each guard releases the levels it holds when dropped. Relocks
+/// known to the analysis go through the guard of the owner; a relock found at runtime gives the
+/// owner another guard with full access, as in C, used by a callee while the guard of its caller
+/// is not dereferenced.
+const PTHREAD_MUTEX: &str = "
+pub struct PthreadMutex<T, const KIND: libc::c_int> {
+    owner: Mutex<Option<(std::thread::ThreadId, usize)>>,
+    cond: Condvar,
+    data: std::cell::UnsafeCell<T>,
+}
+unsafe impl<T: Send, const KIND: libc::c_int> Send for PthreadMutex<T, KIND> {}
+unsafe impl<T: Send, const KIND: libc::c_int> Sync for PthreadMutex<T, KIND> {}
+impl<T, const KIND: libc::c_int> PthreadMutex<T, KIND> {
+    pub const fn new(data: T) -> Self {
+        Self { owner: Mutex::new(None), cond: Condvar::new(), data: std::cell::UnsafeCell::new(data) }
+    }
+    fn state(&self) -> MutexGuard<'_, Option<(std::thread::ThreadId, usize)>> {
+        self.owner.lock().unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+    fn guard(&self) -> PthreadMutexGuard<'_, T, KIND> {
+        PthreadMutexGuard { lock: self, depth: 1, _marker: std::marker::PhantomData }
+    }
+    /// Locks the mutex, failing with `EDEADLK` if an errorcheck mutex is already held by the
+    /// caller.
+    pub fn lock(&self) -> Result<PthreadMutexGuard<'_, T, KIND>, libc::c_int> {
+        let this_thread = std::thread::current().id();
+        let mut state = self.state();
+        if let Some((lock_owner, lock_count)) = &mut *state {
+            if *lock_owner == this_thread {
+                if KIND == libc::PTHREAD_MUTEX_ERRORCHECK {
+                    return Err(libc::EDEADLK);
+                }
+                *lock_count += 1;
+                return Ok(self.guard());
+            }
+        }
+        while state.is_some() {
+            state = self.cond.wait(state).unwrap_or_else(std::sync::PoisonError::into_inner);
+        }
+        *state = Some((this_thread, 1));
+        Ok(self.guard())
+    }
+    pub fn try_lock(&self) -> Result<PthreadMutexGuard<'_, T, KIND>, libc::c_int> {
+        let this_thread = std::thread::current().id();
+        let mut state = self.state();
+        match &mut *state {
+            Some((lock_owner, lock_count))
+                if *lock_owner == this_thread && KIND == libc::PTHREAD_MUTEX_RECURSIVE =>
+            {
+                *lock_count += 1;
+                Ok(self.guard())
+            }
+            Some(_) => Err(libc::EBUSY),
+            None => {
+                *state = Some((this_thread, 1));
+                Ok(self.guard())
+            }
+        }
+    }
+    pub fn get_mut(&mut self) -> Result<&mut T, libc::c_int> {
+        Ok(self.data.get_mut())
+    }
+}
+pub struct PthreadMutexGuard<'a, T, const KIND: libc::c_int> {
+    lock: &'a PthreadMutex<T, KIND>,
+    depth: usize,
+    _marker: std::marker::PhantomData<*const ()>,
+}
+impl<T, const KIND: libc::c_int> PthreadMutexGuard<'_, T, KIND> {
+    /// Relocks the mutex held through this guard, returning `EDEADLK` for an errorcheck mutex.
+    pub fn relock(&mut self) -> libc::c_int {
+        if KIND == libc::PTHREAD_MUTEX_ERRORCHECK {
+            return libc::EDEADLK;
+        }
+        if let Some((_, lock_count)) = &mut *self.lock.state() {
+            *lock_count += 1;
+        }
+        self.depth += 1;
+        0
+    }
+    /// Releases a relock; the last level is released by dropping the guard.
+    pub fn unlock(&mut self) -> libc::c_int {
+        if self.depth == 1 {
+            return libc::EPERM;
+        }
+        if let Some((_, lock_count)) = &mut *self.lock.state() {
+            *lock_count -= 1;
+        }
+        self.depth -= 1;
+        0
+    }
+}
+impl<T, const KIND: libc::c_int> Drop for PthreadMutexGuard<'_, T, KIND> {
+    fn drop(&mut self) {
+        let mut state = self.lock.state();
+        if let Some((_, lock_count)) = &mut *state {
+            *lock_count -= self.depth;
+            if *lock_count == 0 {
+                *state = None;
+                self.lock.cond.notify_one();
+            }
+        }
+    }
+}
+impl<T, const KIND: libc::c_int> std::ops::Deref for PthreadMutexGuard<'_, T, KIND> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+impl<T, const KIND: libc::c_int> std::ops::DerefMut for PthreadMutexGuard<'_, T, KIND> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+pub type RecursiveMutex<T> = PthreadMutex<T, { libc::PTHREAD_MUTEX_RECURSIVE }>;
+pub type RecursiveMutexGuard<'a, T> = PthreadMutexGuard<'a, T, { libc::PTHREAD_MUTEX_RECURSIVE }>;
+pub type ErrorCheckMutex<T> = PthreadMutex<T, { libc::PTHREAD_MUTEX_ERRORCHECK }>;
+pub type ErrorCheckMutexGuard<'a, T> = PthreadMutexGuard<'a, T, { libc::PTHREAD_MUTEX_ERRORCHECK }>;
+";
+
+/// Spinlock replacing `pthread_spinlock_t` when spinlocks are kept.
+const SPIN_LOCK: &str = "
+pub struct SpinLock<T> {
+    locked: std::sync::atomic::AtomicBool,
+    data: std::cell::UnsafeCell<T>,
+}
+unsafe impl<T: Send> Send for SpinLock<T> {}
+unsafe impl<T: Send> Sync for SpinLock<T> {}
+impl<T> SpinLock<T> {
+    pub const fn new(data: T) -> Self {
+        Self { locked: std::sync::atomic::AtomicBool::new(false), data: std::cell::UnsafeCell::new(data) }
+    }
+    fn acquire(&self) -> bool {
+        self.locked
+            .compare_exchange(false, true, std::sync::atomic::Ordering::Acquire, std::sync::atomic::Ordering::Relaxed)
+            .is_ok()
+    }
+    pub fn lock(&self) -> std::sync::LockResult<SpinLockGuard<'_, T>> {
+        while !self.acquire() {
+            while self.locked.load(std::sync::atomic::Ordering::Relaxed) {
+                std::hint::spin_loop();
+            }
+        }
+        Ok(SpinLockGuard { lock: self, _marker: std::marker::PhantomData })
+    }
+    pub fn try_lock(&self) -> Result<SpinLockGuard<'_, T>, libc::c_int> {
+        if self.locked.load(std::sync::atomic::Ordering::Relaxed) || !self.acquire() {
+            return Err(libc::EBUSY);
+        }
+        Ok(SpinLockGuard { lock: self, _marker: std::marker::PhantomData })
+    }
+    pub fn get_mut(&mut self) -> Result<&mut T, libc::c_int> {
+        Ok(self.data.get_mut())
+    }
+}
+pub struct SpinLockGuard<'a, T> {
+    lock: &'a SpinLock<T>,
+    _marker: std::marker::PhantomData<*const ()>,
+}
+impl<T> std::ops::Deref for SpinLockGuard<'_, T> {
+    type Target = T;
+    fn deref(&self) -> &T {
+        unsafe { &*self.lock.data.get() }
+    }
+}
+impl<T> std::ops::DerefMut for SpinLockGuard<'_, T> {
+    fn deref_mut(&mut self) -> &mut T {
+        unsafe { &mut *self.lock.data.get() }
+    }
+}
+impl<T> Drop for SpinLockGuard<'_, T> {
+    fn drop(&mut self) {
+        self.lock.locked.store(false, std::sync::atomic::Ordering::Release);
+    }
+}
+";
+
+/// Converts an absolute `timespec` deadline into the timeout remaining on `clock`.
+const DEADLINE_TIMEOUT: &str = "
+fn deadline_timeout(clock: libc::clockid_t, tv_sec: i64, tv_nsec: i64) -> Duration {
+    let mut now = libc::timespec { tv_sec: 0, tv_nsec: 0 };
+    unsafe { libc::clock_gettime(clock, &mut now) };
+    let deadline = Duration::new(tv_sec.max(0) as u64, tv_nsec.clamp(0, 999_999_999) as u32);
+    deadline.saturating_sub(Duration::new(now.tv_sec as u64, now.tv_nsec as u32))
+}
+";
+
+/// Acquisition of a lock by `pthread_mutex_timedlock` and `pthread_rwlock_timed{rd,wr}lock`.
+const TIMED_LOCK: &str = "
+/// Retries `try_lock` until `timeout` passes, sleeping between attempts for intervals doubling up
+/// to a millisecond. Unlike the blocking C call, a released lock is not handed over to the
+/// waiting thread, which may acquire it up to an interval late or lose it to other threads.
+fn timed_lock<G, E>(timeout: Duration, mut try_lock: impl FnMut() -> Result<G, E>) -> Option<G> {
+    let deadline = std::time::Instant::now() + timeout;
+    let mut backoff = Duration::from_micros(1);
+    loop {
+        if let Ok(guard) = try_lock() {
+            return Some(guard);
+        }
+        let now = std::time::Instant::now();
+        if now >= deadline {
+            return None;
+        }
+        std::thread::park_timeout(backoff.min(deadline - now));
+        backoff = (backoff * 2).min(Duration::from_millis(1));
+    }
+}
+";
+
+/// Counting semaphore replacing `sem_t`; methods follow the `sem_*` return conventions, failing
+/// with `-1` and `errno` set.
+const SEMAPHORE: &str = "
+pub struct Semaphore {
+    count: Mutex<libc::c_uint>,
+    cond: Condvar,
+}
+impl Semaphore {
+    pub const fn new(value: libc::c_uint) -> Self {
+        Self { count: Mutex::new(value), cond: Condvar::new() }
+    }
+    fn fail(errno: libc::c_int) -> libc::c_int {
+        unsafe { *libc::__errno_location() = errno };
+        -1
+    }
+    pub fn wait(&self) -> libc::c_int {
+        let mut count = self.count.lock().unwrap();
+        while *count == 0 {
+            count = self.cond.wait(count).unwrap();
+        }
+        *count -= 1;
+        0
+    }
+    pub fn try_wait(&self) -> libc::c_int {
+        let mut count = self.count.lock().unwrap();
+        if *count == 0 {
+            return Self::fail(libc::EAGAIN);
+        }
+        *count -= 1;
+        0
+    }
+    pub fn timed_wait(&self, timeout: Duration) -> libc::c_int {
+        let count = self.count.lock().unwrap();
+        let (mut count, res) = self.cond.wait_timeout_while(count, timeout, |c| *c == 0).unwrap();
+        if res.timed_out() {
+            return Self::fail(libc::ETIMEDOUT);
+        }
+        *count -= 1;
+        0
+    }
+    pub fn post(&self) -> libc::c_int {
+        *self.count.lock().unwrap() += 1;
+        self.cond.notify_one();
+        0
+    }
+    pub fn value(&self) -> libc::c_int {
+        *self.count.lock().unwrap() as libc::c_int
+    }
+}
+";
+
+const THREAD_SPECIFIC: &str = "
+pub struct ThreadSpecific {
+    value: std::cell::Cell<*mut libc::c_void>,
+    dtor: Option<unsafe extern \"C\" fn(*mut libc::c_void) -> ()>,
+}
+impl ThreadSpecific {
+    pub const fn new(dtor: Option<unsafe extern \"C\" fn(*mut libc::c_void) -> ()>) -> Self {
+        Self { value: std::cell::Cell::new(std::ptr::null_mut()), dtor }
+    }
+    pub fn get(&self) -> *mut libc::c_void {
+        self.value.get()
+    }
+    pub fn set(&self, value: *mut libc::c_void) {
+        self.value.set(value)
+    }
+}
+impl Drop for ThreadSpecific {
+    fn drop(&mut self) {
+        let value = self.value.replace(std::ptr::null_mut());
+        if let (false, Some(dtor)) = (value.is_null(), self.dtor) {
+            unsafe { dtor(value) };
+        }
+    }
+}
+";
+
+const THRD_CODES: &str = "
+pub const thrd_success: libc::c_int = 0;
+pub const thrd_busy: libc::c_int = 1;
+pub const thrd_error: libc::c_int = 2;
+pub const thrd_nomem: libc::c_int = 3;
+pub const thrd_timedout: libc::c_int = 4;
+";
+
+const BARRIER: &str = "Option<Barrier>";
+
+const JOIN_HANDLE: &str = "Option<std::thread::JoinHandle<usize>>";
+
+/// A call from a caller to a callee, with the paths of the arguments and the lines of the call.
+type CallSite = (String, String, Vec<Option<ExprPath>>, BTreeSet<usize>);
+
+/// A `pthread_t` slot passed to `pthread_create`, retyped to hold a `JoinHandle`.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+enum ThreadSlot {
+    Global(String),
+    Local(String, String),
+    Field(String, String),
+}
+
+/// Checks if the place `e` is the thread argument of `pthread_create`, `pthread_join` or
+/// `pthread_detach`, the only uses translated for retyped slots, or an array indexed for it,
+/// directly or by `offset`.
+fn is_thread_arg<'tcx>(ctx: &LateContext<'tcx>, e: &'tcx Expr<'tcx>) -> bool {
+    let hir = ctx.tcx.hir();
+    let mut id = e.hir_id;
+    loop {
+        let p = match hir.find(hir.get_parent_node(id)) {
+            Some(Node::Expr(p)) => p,
+            _ => return false,
+        };
+        match &p.kind {
+            ExprKind::Index(a, _) if a.hir_id == id => id = p.hir_id,
+            // `&mut *a.as_mut_ptr().offset(i)`
+            ExprKind::MethodCall(m, args, _)
+                if args[0].hir_id == id
+                    && matches!(
+                        m.ident.name.to_ident_string().as_str(),
+                        "as_mut_ptr" | "offset"
+                    ) =>
+            {
+                id = p.hir_id
+            }
+            ExprKind::AddrOf(_, _, _)
+            | ExprKind::Unary(UnOp::Deref, _)
+            | ExprKind::Cast(_, _)
+            | ExprKind::DropTemps(_) => id = p.hir_id,
+            ExprKind::Call(f, args) => {
+                let is_thread_call = matches!(
+                    api_name(f).as_deref(),
+                    Some("pthread_create" | "pthread_join" | "pthread_detach")
+                );
+                let args = call_args(f, args);
+                return is_thread_call && args.first().map_or(false, |a| a.hir_id == id);
+            }
+            _ => return false,
+        }
+    }
+}
+
+/// Checks if `e` is the once argument of `pthread_once`, the only use translated for `Once`.
+fn is_once_arg<'tcx>(ctx: &LateContext<'tcx>, e: &'tcx Expr<'tcx>) -> bool {
+    let hir = ctx.tcx.hir();
+    let mut id = e.hir_id;
+    loop {
+        let p = match hir.find(hir.get_parent_node(id)) {
+            Some(Node::Expr(p)) => p,
+            _ => return false,
+        };
+        match &p.kind {
+            ExprKind::AddrOf(_, _, _) | ExprKind::Cast(_, _) | ExprKind::DropTemps(_) => {
+                id = p.hir_id
+            }
+            ExprKind::Call(f, args) => {
+                let args = call_args(f, args);
+                return api_name(f).as_deref() == Some("pthread_once")
+                    && args.first().map_or(false, |a| a.hir_id == id);
+            }
+            _ => return false,
+        }
+    }
+}
+
+fn thread_slot<'tcx>(
+    ctx: &LateContext<'tcx>,
+    func: &str,
+    e: &'tcx Expr<'tcx>,
+) -> Option<ThreadSlot> {
+    match &e.kind {
+        ExprKind::Path(QPath::Resolved(_, p)) => match p.res {
+            Res::Local(_) => Some(ThreadSlot::Local(func.to_string(), path_to_string(p))),
+            Res::Def(DefKind::Static(_), _) => Some(ThreadSlot::Global(path_to_string(p))),
+            _ => None,
+        },
+        ExprKind::Index(a, _) => thread_slot(ctx, func, a),
+        ExprKind::Field(s, f) => {
+            let ty = type_to_string(unwrap_ptr_from_type(type_of(ctx, s.hir_id)));
+            Some(ThreadSlot::Field(ty, f.name.to_ident_string()))
+        }
+        _ => offset_element(e).and_then(|(a, _)| thread_slot(ctx, func, a)),
+    }
+}
+
+/// A global `pthread_key_t`, translated into a `thread_local!` when created exactly once.
+fn thread_key(ctx: &LateContext<'_>, e: &Expr<'_>) -> Option<String> {
+    let e = unwrap_addr(unwrap_cast_recursively(e));
+    match resolve_path(ctx, e)? {
+        Res::Def(DefKind::Static(_), _) => Some(span_to_string(ctx, e.span)),
+        _ => None,
+    }
+}
+
+/// The destructor passed to `pthread_key_create` as the argument of `ThreadSpecific::new`: `None`
+/// or a function; other expressions are not translated.
+fn key_dtor(ctx: &LateContext<'_>, e: &Expr<'_>) -> Option<String> {
+    let f = match &e.kind {
+        ExprKind::Call(c, args) if args.len() == 1 && span_to_string(ctx, c.span) == "Some" => {
+            unwrap_cast_recursively(&args[0])
+        }
+        ExprKind::Path(_) if span_to_string(ctx, e.span) == "None" => {
+            return Some("None".to_string())
+        }
+        _ => return None,
+    };
+    matches!(resolve_path(ctx, f), Some(Res::Def(DefKind::Fn, _)))
+        .then(|| format!("Some({})", span_to_string(ctx, f.span)))
+}
+
+/// The deadline passed to a timed wait, as it appears in `tv_sec += x`.
+fn deadline_name(ctx: &LateContext<'_>, e: &Expr<'_>) -> String {
+    span_to_string(ctx, unwrap_addr(unwrap_cast_recursively(e)).span)
+}
+
+fn is_semaphore(e: &Expr<'_>) -> bool {
+    matches!(unwrap_cast_recursively(e).kind, ExprKind::AddrOf(_, _, _))
+}
+
+/// The semaphore passed to a `sem_*` call: a borrowed place, or the target of a pointer, which is
+/// a `Semaphore` as the `sem_t` alias itself is retyped.
+fn semaphore_arg(ctx: &LateContext<'_>, e: &Expr<'_>) -> String {
+    if is_semaphore(e) {
+        return normalize_arg(ctx, e).0;
+    }
+    let p = unwrap_cast_recursively(e);
+    let s = span_to_string(ctx, p.span);
+    if matches!(p.kind, ExprKind::Path(_)) {
+        format!("(*{})", s)
+    } else {
+        format!("(*({}))", s)
+    }
+}
+
+fn hid_to_string(ctx: &LateContext<'_>, hid: HirId) -> String {
+    span_to_string(ctx, ctx.tcx.hir().span(hid))
 }
 
 fn current_function(ctx: &LateContext<'_>, hid: HirId) -> Option<String> {
@@ -1479,6 +3812,21 @@ fn remove_attributes(ctx: &LateContext<'_>, i: &Item<'_>) {
     }
 }
 
+/// The `#[derive]` attribute generating the item at `span`; c2rust derives `Copy` and `Clone`
+/// together, and neither applies to lock-bearing structs.
+fn derive_attribute(span: Span) -> Option<Span> {
+    let mut expn = span.ctxt().outer_expn_data();
+    loop {
+        if expn.kind == ExpnKind::Macro(MacroKind::Attr, sym::derive) {
+            return Some(expn.call_site);
+        }
+        if expn.parent == ExpnId::root() {
+            return None;
+        }
+        expn = expn.parent.expn_data();
+    }
+}
+
 fn path_to_symbol<'tcx>(p: &'tcx Path<'tcx>) -> Symbol {
     p.segments.last().unwrap().ident.name
 }
@@ -1498,6 +3846,407 @@ fn name<'tcx>(e: &'tcx Expr<'tcx>) -> Option<String> {
     name_symbol(e).map(|s| s.to_ident_string())
 }
 
+/// The value and the code it is compared to in `if x == ETIMEDOUT { break; }`.
+fn timeout_break<'a, 'tcx>(e: &'a Expr<'tcx>) -> Option<(&'a Expr<'tcx>, &'a Expr<'tcx>)> {
+    let (c, t) = match e.kind {
+        ExprKind::If(c, t, None) => (c, t),
+        _ => return None,
+    };
+    match t.kind {
+        ExprKind::Block(
+            Block {
+                stmts:
+                    [Stmt {
+                        kind: StmtKind::Semi(b),
+                        ..
+                    }],
+                expr: None,
+                ..
+            },
+            _,
+        ) if matches!(b.kind, ExprKind::Break(_, None)) => (),
+        _ => return None,
+    }
+    let (x, v) = match unwrap_drop_temps(c).kind {
+        ExprKind::Binary(op, x, v) if op.node == BinOpKind::Eq => (x, v),
+        _ => return None,
+    };
+    Some((unwrap_cast_recursively(x), unwrap_cast_recursively(v)))
+}
+
+/// Checks if `e` is the code of a timed out wait of an API returning `code`: `ETIMEDOUT`, by name
+/// or by the number the macro expands to, or `thrd_timedout`.
+fn is_timeout_code(ctx: &LateContext<'_>, e: &Expr<'_>, code: ReturnCode) -> bool {
+    let s = span_to_string(ctx, e.span);
+    let name = s.rsplit("::").next();
+    match code {
+        ReturnCode::Errno => {
+            name == Some("ETIMEDOUT")
+                || s.parse().map_or(false, |n| {
+                    std::io::Error::from_raw_os_error(n).kind() == std::io::ErrorKind::TimedOut
+                })
+        }
+        ReturnCode::C11 => name == Some("thrd_timedout"),
+        ReturnCode::Void => false,
+    }
+}
+
+/// Checks if `e` reads data protected by the lock at `mutex`.
+fn reads_protected<'tcx>(ctx: &LateContext<'tcx>, e: &'tcx Expr<'tcx>, mutex: &ExprPath) -> bool {
+    struct ReadVisitor<'a, 'tcx> {
+        ctx: &'a LateContext<'tcx>,
+        mutex: &'a ExprPath,
+        found: bool,
+    }
+
+    impl<'tcx> intravisit::Visitor<'tcx> for ReadVisitor<'_, 'tcx> {
+        fn visit_expr(&mut self, e: &'tcx Expr<'tcx>) {
+            let lock = match &e.kind {
+                ExprKind::Path(_) => name(e)
+                    .and_then(|x| global_mutex_map().get(&x))
+                    .map(|m| ExprPath::new(m.clone(), vec![])),
+                ExprKind::Index(a, i) => name(a).and_then(|a| array_mutex_map().get(&a)).map(|m| {
+                    let i = span_to_string(self.ctx, unwrap_cast_recursively(i).span);
+                    ExprPath::new(m.clone(), vec![ExprPathProj::Index(i)])
+                }),
+                ExprKind::Field(s, f) => {
+                    let ty = type_to_string(unwrap_ptr_from_type(type_of(self.ctx, s.hir_id)));
+                    let m = struct_mutex_map()
+                        .get(&ty)
+                        .and_then(|map| map.get(&f.name.to_ident_string()));
+                    m.zip(expr_to_path(self.ctx, s)).map(|(m, mut path)| {
+                        path.add_suffix(ExprPathProj::Field(m.clone()));
+                        path
+                    })
+                }
+                _ => None,
+            };
+            self.found |= lock.as_ref() == Some(self.mutex);
+            intravisit::walk_expr(self, e);
+        }
+    }
+
+    let mut visitor = ReadVisitor {
+        ctx,
+        mutex,
+        found: false,
+    };
+    intravisit::Visitor::visit_expr(&mut visitor, e);
+    visitor.found
+}
+
+/// The name of a called function, naming C11 and user-declared lock functions after their
+/// pthread counterparts.
+fn api_name<'tcx>(e: &'tcx Expr<'tcx>) -> Option<String> {
+    name(e).map(|f| pthread_function(&f).map_or(f, str::to_string))
+}
+
+/// Checks if `t` is a raw pointer type; function pointers, which mention raw pointers in their
+/// signatures, are `Send`.
+fn is_raw_pointer(t: &str) -> bool {
+    t.contains('*') && !t.contains("fn(")
+}
+
+/// The lock global whose data `e` accesses, with the index for arrays of locks.
+fn global_lock_of(ctx: &LateContext<'_>, e: &Expr<'_>) -> Option<ExprPath> {
+    match e.kind {
+        ExprKind::Path(_) => {
+            let m = global_mutex_map().get(&name(e)?)?;
+            Some(ExprPath::new(m.clone(), vec![]))
+        }
+        ExprKind::Index(a, i) => {
+            let m = array_mutex_map().get(&name(a)?)?;
+            let i = span_to_string(ctx, unwrap_cast_recursively(i).span);
+            Some(ExprPath::new(m.clone(), vec![ExprPathProj::Index(i)]))
+        }
+        _ => None,
+    }
+}
+
+/// Checks if the lock at `mutex` is held at `lines` of `func`, where its data is reached by
+/// guards.
+fn is_held(func: &str, mutex: &ExprPath, lines: &BTreeSet<usize>) -> bool {
+    let summary = some_or!(function_mutex_map().get(func), return false);
+    [
+        &summary.mutex_line,
+        &summary.rdlock_line,
+        &summary.wrlock_line,
+    ]
+    .iter()
+    .any(|map| {
+        map.get(mutex)
+            .map_or(false, |ls| lines.iter().any(|l| ls.contains(l)))
+    })
+}
+
+/// Checks if the held lock `h` may be the lock `m`: globals by name, and fields by the name of
+/// the field, whatever the struct they are reached through.
+fn same_lock(h: &ExprPath, m: &ExprPath) -> bool {
+    let field = |p: &ExprPath| {
+        p.projections.iter().rev().find_map(|p| match p {
+            ExprPathProj::Field(f) => Some(f.clone()),
+            _ => None,
+        })
+    };
+    match (field(h), field(m)) {
+        (None, None) => h.base == m.base,
+        (Some(f), Some(g)) => f == g,
+        _ => false,
+    }
+}
+
+/// How a temporary guard reaches data outside critical sections, or a temporary borrow reaches a
+/// thread-local cell.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum TemporaryAccess {
+    /// a read, copied out of a guard dropped right away
+    Read,
+    /// the place assigned by a statement, whose guard is dropped at its end
+    Write,
+}
+
+/// How a temporary guard can reach the data accessed by `e`; `None` if the data is borrowed, or
+/// assigned by a nested assignment or at an index that may access data itself, where the guard
+/// would outlive the access or be held while locking again.
+fn temporary_access(ctx: &LateContext<'_>, e: &Expr<'_>) -> Option<TemporaryAccess> {
+    let hir = ctx.tcx.hir();
+    let mut id = e.hir_id;
+    let mut indices = vec![];
+    loop {
+        let p = match hir.find(hir.get_parent_node(id)) {
+            Some(Node::Expr(p)) => p,
+            _ => return Some(TemporaryAccess::Read),
+        };
+        match p.kind {
+            ExprKind::Field(b, _) if b.hir_id == id => (),
+            ExprKind::Index(b, i) if b.hir_id == id => indices.push(i),
+            ExprKind::AddrOf(_, _, _) => return None,
+            ExprKind::MethodCall(_, args, _) if args[0].hir_id == id => return None,
+            ExprKind::Assign(l, _, _) | ExprKind::AssignOp(_, l, _) if l.hir_id == id => {
+                let stmt = matches!(
+                    hir.find(hir.get_parent_node(p.hir_id)),
+                    Some(Node::Stmt(Stmt {
+                        kind: StmtKind::Semi(_),
+                        ..
+                    }))
+                );
+                let simple = indices.iter().all(|i| {
+                    let i = unwrap_cast_recursively(i);
+                    matches!(i.kind, ExprKind::Lit(_))
+                        || matches!(resolve_path(ctx, i), Some(Res::Local(_)))
+                });
+                return (stmt && simple).then_some(TemporaryAccess::Write);
+            }
+            _ => return Some(TemporaryAccess::Read),
+        }
+        id = p.hir_id;
+    }
+}
+
+/// The data `x` of the lock `lock` accessed by `e` outside critical sections: by `get_mut` unless
+/// `safe`, and otherwise by a temporary guard, dropped once a read is copied out or at the end
+/// of an assignment.
+fn unlocked_data(
+    ctx: &LateContext<'_>,
+    e: &Expr<'_>,
+    lock: &str,
+    rwlock: bool,
+    safe: bool,
+    x: &str,
+) -> String {
+    if !safe {
+        return format!("{}.get_mut().unwrap().{}", lock, x);
+    }
+    match temporary_access(ctx, e) {
+        Some(TemporaryAccess::Write) => {
+            let method = if rwlock { "write" } else { "lock" };
+            format!("{}.{}().unwrap().{}", lock, method, x)
+        }
+        _ => {
+            let method = if rwlock { "read" } else { "lock" };
+            format!(
+                "{{ let value = {}.{}().unwrap().{}; value }}",
+                lock, method, x
+            )
+        }
+    }
+}
+
+/// Rewrites `e`, which sets the value `v`, into `set`, which uses `v` as `value` in the closure of
+/// a thread-local's `with`. The value is bound first, optionally cast, as evaluating it in the
+/// closure would borrow a cell again when it reads the same thread-local.
+fn bind_value(ctx: &LateContext<'_>, e: &Expr<'_>, v: &Expr<'_>, cast: Option<&str>, set: &str) {
+    let (open, close) = match cast {
+        Some(ty) => ("(".to_string(), format!(") as {}", ty)),
+        None => (String::new(), String::new()),
+    };
+    add_replacement(
+        ctx,
+        e.span.until(v.span),
+        format!("{{ let value = {}", open),
+    );
+    let new_e = format!("{}; {} }}", close, set);
+    add_replacement(ctx, e.span.with_lo(v.span.hi()), new_e);
+}
+
+fn static_of(safe: bool) -> &'static str {
+    if safe {
+        "static"
+    } else {
+        "static mut"
+    }
+}
+
+/// How a field of a struct built by `fresh_struct` is accessed.
+#[derive(Clone, Copy)]
+enum FieldAccess<'a> {
+    Plain,
+    Atomic,
+    /// protected by the lock at the path, of the type
+    Locked(&'a str, &'static str),
+}
+
+/// A read of the field `f` of the data of `lock`, of the type `lock_ty`, under a temporary guard.
+fn locked_read(lock: &str, lock_ty: &str, f: &str) -> String {
+    match lock_ty {
+        "RwLock" => format!("{}.read().unwrap().{}", lock, f),
+        _ => format!("{}.lock().unwrap().{}", lock, f),
+    }
+}
+
+/// Checks if `t` is a synchronization primitive that cannot be copied once translated.
+fn is_sync_type(t: &str) -> bool {
+    t == "pthread_mutex_t"
+        || t == "pthread_spinlock_t"
+        || t == "pthread_rwlock_t"
+        || t == "pthread_cond_t"
+        || t == "sem_t"
+        || t == "pthread_barrier_t"
+}
+
+/// Checks if `size` is `size_of::<T>()` of the type `ty`, the size of a single value.
+fn is_size_of(ctx: &LateContext<'_>, size: &Expr<'_>, ty: &str) -> bool {
+    let f = match &unwrap_cast_recursively(size).kind {
+        ExprKind::Call(f, []) => f,
+        _ => return false,
+    };
+    let def_id = some_or!(
+        resolve_path(ctx, f).and_then(|r| r.opt_def_id()),
+        return false
+    );
+    ctx.match_def_path(def_id, &[sym::core, sym::mem, sym::size_of])
+        && ctx
+            .typeck_results()
+            .node_substs(f.hir_id)
+            .types()
+            .next()
+            .map_or(false, |t| type_to_string(t) == ty)
+}
+
+/// The innermost element type of the array type `t`.
+fn array_element(t: &str) -> Option<&str> {
+    let mut elem = t;
+    while let Some((e, _)) = elem.strip_prefix('[').and_then(|t| t.rsplit_once(';')) {
+        elem = e.trim();
+    }
+    (elem != t).then(|| elem)
+}
+
+/// All-zero bytes as a value of type `t`; arrays of synchronization primitives or thread slots
+/// are rejected by `unzeroable_array` beforehand.
+fn zero_value(t: &str) -> String {
+    if t.starts_with('[') {
+        format!("std::mem::transmute([0u8; std::mem::size_of::<{}>()])", t)
+    } else {
+        default_value(t)
+    }
+}
+
+/// Drops a lock written in place by its init, as the allocated memory holding it is freed
+/// without running destructors; other locks, including locals and statics, are dropped by Rust.
+fn destroy_lock<'tcx>(
+    ctx: &LateContext<'tcx>,
+    func: &str,
+    e: &'tcx Expr<'tcx>,
+    lock: &'tcx Expr<'tcx>,
+) -> String {
+    let path = some_or!(expr_to_path(ctx, lock), return "0".to_string());
+    let place = unwrap_addr(unwrap_cast_recursively(lock));
+    let allocated = alloc_map()
+        .get(func)
+        .map_or(false, |xs| xs.contains(&path.base));
+    let uninit = lock_key(ctx, lock).map_or(false, |key| uninit_locks().contains(&key));
+    if !is_deref_place(place) || !allocated || !uninit {
+        return "0".to_string();
+    }
+    let new_e = format!(
+        "std::ptr::drop_in_place(std::ptr::addr_of_mut!({}))",
+        span_to_string(ctx, place.span)
+    );
+    if result_used(ctx, e) {
+        format!("{{ {}; 0 }}", new_e)
+    } else {
+        new_e
+    }
+}
+
+/// Checks if a place is reached through a raw pointer.
+fn is_deref_place(e: &Expr<'_>) -> bool {
+    match &e.kind {
+        ExprKind::Unary(UnOp::Deref, _) => true,
+        ExprKind::MethodCall(m, _, _) => m.ident.to_string() == "offset",
+        ExprKind::Field(e, _) | ExprKind::Index(e, _) => is_deref_place(e),
+        _ => false,
+    }
+}
+
+/// The arguments of a call in the order of its pthread counterpart.
+fn call_args<'tcx>(func: &'tcx Expr<'tcx>, args: &'tcx [Expr<'tcx>]) -> Vec<&'tcx Expr<'tcx>> {
+    let args: Vec<_> = args.iter().collect();
+    match name(func).and_then(|f| lock_api(&f)) {
+        Some(api) => api.reorder(&args),
+        None => args,
+    }
+}
+
+/// The return-code convention of a called function.
+fn return_code<'tcx>(e: &'tcx Expr<'tcx>) -> ReturnCode {
+    let f = some_or!(name(e), return ReturnCode::Errno);
+    if let Some(api) = lock_api(&f) {
+        api.ret
+    } else if c11_function(&f).is_some() {
+        ReturnCode::C11
+    } else {
+        ReturnCode::Errno
+    }
+}
+
+/// The return code of a successful call.
+fn ok_code(code: ReturnCode) -> &'static str {
+    match code {
+        ReturnCode::Errno => "0",
+        ReturnCode::C11 => "thrd_success as libc::c_int",
+        ReturnCode::Void => "()",
+    }
+}
+
+/// The return code of a failed call, mapping errno values to C11 codes when needed.
+fn ret_code(code: ReturnCode, errno: &str) -> String {
+    match code {
+        ReturnCode::Errno => format!("libc::{}", errno),
+        ReturnCode::C11 => {
+            let code = match errno {
+                "EBUSY" => "thrd_busy",
+                "ETIMEDOUT" => "thrd_timedout",
+                "ENOMEM" => "thrd_nomem",
+                _ => "thrd_error",
+            };
+            format!("{} as libc::c_int", code)
+        }
+        ReturnCode::Void => "()".to_string(),
+    }
+}
+
 fn unwrap_addr<'a, 'tcx>(e: &'a Expr<'tcx>) -> &'a Expr<'tcx> {
     match &e.kind {
         ExprKind::AddrOf(_, _, e) => e,
@@ -1509,33 +4258,41 @@ fn normalize_arg<'a, 'b, 'tcx>(ctx: &'a LateContext<'b>, e: &'tcx Expr<'tcx>) ->
     let path = expr_to_path(ctx, e).unwrap();
     let guard = path.guard();
     let arg = unwrap_addr(unwrap_cast_recursively(e));
-    match &arg.kind {
-        ExprKind::Unary(
-            UnOp::Deref,
-            Expr {
-                kind: ExprKind::MethodCall(method, args, _),
-                ..
-            },
-        ) => {
-            assert_eq!(method.ident.name.to_ident_string(), "offset");
-            assert_eq!(args.len(), 2);
-            let arr = match &args[0].kind {
-                ExprKind::MethodCall(method, args, _) => {
-                    assert_eq!(method.ident.name.to_ident_string(), "as_mut_ptr");
-                    assert_eq!(args.len(), 1);
-                    name(&args[0]).unwrap()
-                }
-                _ => unreachable!(),
-            };
-            let ind = unwrap_cast_recursively(&args[1]);
-            let ind = span_to_string(ctx, ind.span);
-            let arg = format!("{}[{} as usize]", arr, ind);
-            (arg, guard)
+    (element_place(ctx, arg), guard)
+}
+
+/// The array and the index of `*a.as_mut_ptr().offset(i)` or `a.as_mut_ptr().offset(i)`, which
+/// c2rust emits for `&a[i]`.
+fn offset_element<'a, 'tcx>(e: &'a Expr<'tcx>) -> Option<(&'a Expr<'tcx>, &'a Expr<'tcx>)> {
+    let e = match &e.kind {
+        ExprKind::Unary(UnOp::Deref, e) => *e,
+        _ => e,
+    };
+    let (method, args) = match &e.kind {
+        ExprKind::MethodCall(method, args, _) => (method, args),
+        _ => return None,
+    };
+    if method.ident.name.to_ident_string() != "offset" || args.len() != 2 {
+        return None;
+    }
+    match &args[0].kind {
+        ExprKind::MethodCall(method, arr, _)
+            if method.ident.name.to_ident_string() == "as_mut_ptr" && arr.len() == 1 =>
+        {
+            Some((&arr[0], &args[1]))
         }
-        _ => {
-            let arg = span_to_string(ctx, arg.span);
-            (arg, guard)
+        _ => None,
+    }
+}
+
+/// The place `e` with array elements reached by `offset` written as `a[i as usize]`.
+fn element_place(ctx: &LateContext<'_>, e: &Expr<'_>) -> String {
+    match offset_element(e) {
+        Some((arr, ind)) => {
+            let ind = span_to_string(ctx, unwrap_cast_recursively(ind).span);
+            format!("{}[{} as usize]", span_to_string(ctx, arr.span), ind)
         }
+        None => span_to_string(ctx, e.span),
     }
 }
 
@@ -1556,11 +4313,78 @@ fn in_assignment<'tcx>(ctx: &LateContext<'tcx>, e: &Expr<'tcx>, lhs: bool) -> bo
     }
 }
 
-fn read_condition<'a, 'tcx>(ctx: &LateContext<'tcx>, e: &'a Expr<'tcx>) -> Option<(String, bool)> {
-    let e = match e.kind {
+/// Whether the value of `e` is consumed, i.e., `e` is not an expression statement.
+fn result_used<'tcx>(ctx: &LateContext<'tcx>, e: &Expr<'tcx>) -> bool {
+    let hir = ctx.tcx.hir();
+    match hir.get(hir.get_parent_node(e.hir_id)) {
+        Node::Stmt(_) => false,
+        // the tail of a block is consumed if the block is
+        Node::Block(b) => match hir.get(hir.get_parent_node(b.hir_id)) {
+            Node::Expr(be) => result_used(ctx, be),
+            _ => true,
+        },
+        _ => true,
+    }
+}
+
+fn unwrap_drop_temps<'a, 'tcx>(e: &'a Expr<'tcx>) -> &'a Expr<'tcx> {
+    match e.kind {
         ExprKind::DropTemps(e) => e,
         _ => e,
+    }
+}
+
+/// A direct call to a trylock function, as an `Option` of a guard and the name of the guard.
+fn trylock_call<'tcx>(
+    ctx: &LateContext<'tcx>,
+    e: &'tcx Expr<'tcx>,
+    write_rwlocks: &BTreeSet<String>,
+) -> Option<(String, String)> {
+    let (func, args) = match unwrap_cast_recursively(e).kind {
+        ExprKind::Call(func, args) => (func, args),
+        _ => return None,
     };
+    let name = api_name(func)?;
+    if !matches!(
+        name.as_str(),
+        "pthread_mutex_trylock"
+            | "pthread_spin_trylock"
+            | "pthread_rwlock_tryrdlock"
+            | "pthread_rwlock_trywrlock"
+    ) {
+        return None;
+    }
+    let args = call_args(func, args);
+    let m = lock_key(ctx, args[0]);
+    let write = m.as_ref().map_or(false, |m| write_rwlocks.contains(m));
+    let method = match name.as_str() {
+        "pthread_rwlock_tryrdlock" if !write => "try_read",
+        "pthread_rwlock_tryrdlock" | "pthread_rwlock_trywrlock" => "try_write",
+        _ => "try_lock",
+    };
+    if m.map_or(false, |m| promoted_map().values().any(|x| *x == m)) {
+        return None;
+    }
+    let (arg, guard) = normalize_arg(ctx, args[0]);
+    Some((format!("{}.{}().ok()", arg, method), guard))
+}
+
+/// The leaves of a chain of `op`.
+fn chain_operands<'a, 'tcx>(e: &'a Expr<'tcx>, op: BinOpKind, operands: &mut Vec<&'a Expr<'tcx>>) {
+    match unwrap_drop_temps(e).kind {
+        ExprKind::Binary(op1, lhs, rhs) if op1.node == op => {
+            chain_operands(lhs, op, operands);
+            chain_operands(rhs, op, operands);
+        }
+        _ => operands.push(e),
+    }
+}
+
+fn read_condition<'a, 'tcx>(
+    ctx: &LateContext<'tcx>,
+    e: &'a Expr<'tcx>,
+) -> Option<(&'a Expr<'tcx>, bool)> {
+    let e = unwrap_drop_temps(e);
     let (e, not) = match e.kind {
         ExprKind::Unary(op, e) => {
             if op == UnOp::Not {
@@ -1580,19 +4404,25 @@ fn read_condition<'a, 'tcx>(ctx: &LateContext<'tcx>, e: &'a Expr<'tcx>) -> Optio
             };
             let lhs = unwrap_cast_recursively(lhs);
             let rhs = unwrap_cast_recursively(rhs);
-            let e = if span_to_string(ctx, lhs.span) == "0" {
+            let e = if is_success_code(ctx, lhs) {
                 rhs
-            } else if span_to_string(ctx, rhs.span) == "0" {
+            } else if is_success_code(ctx, rhs) {
                 lhs
             } else {
                 return None;
             };
-            Some((span_to_string(ctx, e.span), not ^ eq))
+            Some((e, not ^ eq))
         }
         _ => None,
     }
 }
 
+/// Checks if `e` is `0` or `thrd_success`, which C11 functions return on success.
+fn is_success_code(ctx: &LateContext<'_>, e: &Expr<'_>) -> bool {
+    let s = span_to_string(ctx, e.span);
+    s == "0" || s.rsplit("::").next() == Some("thrd_success")
+}
+
 fn path_to_id(p: &str) -> String {
     p.split(&[' ', '-', '>', '(', ')', '[', ']', '.', '*', '&'])
         .filter(|s| !s.is_empty())
@@ -0,0 +1,258 @@
+use etrace::some_or;
+use rustc_hir::{
+    def::{DefKind, Res},
+    *,
+};
+use rustc_lint::{LateContext, LateLintPass, LintPass};
+
+use super::{add_replacement, atomic_map, promoted_map, unwrap_addr};
+use crate::{
+    callback::LatePass,
+    util::{
+        is_atomic_intrinsic, lock_key, resolve_path, span_to_string, type_of, type_to_string,
+        unwrap_cast_recursively,
+    },
+};
+
+/// Retypes variables found by the atomic analysis and lowers intrinsics to atomic methods.
+#[derive(Default)]
+pub(super) struct AtomicPass;
+
+impl AtomicPass {
+    #[allow(clippy::new_ret_no_self)]
+    pub(super) fn new() -> Box<LatePass> {
+        Box::new(Self::default())
+    }
+}
+
+impl LintPass for AtomicPass {
+    fn name(&self) -> &'static str {
+        "AtomicPass"
+    }
+}
+
+impl<'tcx> LateLintPass<'tcx> for AtomicPass {
+    fn check_item(&mut self, ctx: &LateContext<'tcx>, i: &'tcx Item<'tcx>) {
+        match &i.kind {
+            ItemKind::Static(_, _, b) => {
+                let name = i.ident.name.to_ident_string();
+                let ty = some_or!(atomic_map().get(&name), return);
+                let init = span_to_string(ctx, ctx.tcx.hir().body(*b).value.span);
+                let new_i = format!(
+                    "pub static {}: std::sync::atomic::{} = {};",
+                    name,
+                    ty,
+                    atomic_new(ty, init)
+                );
+                add_replacement(ctx, i.span, new_i);
+            }
+            ItemKind::Struct(VariantData::Struct(fs, _), _) => {
+                let s = i.ident.name.to_ident_string();
+                for f in fs.iter() {
+                    let key = format!("{}.{}", s, f.ident.name.to_ident_string());
+                    let ty = some_or!(atomic_map().get(&key), continue);
+                    let new_ty = format!("std::sync::atomic::{}", ty);
+                    add_replacement(ctx, f.ty.span, new_ty);
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn check_expr(&mut self, ctx: &LateContext<'tcx>, e: &'tcx Expr<'tcx>) {
+        match &e.kind {
+            ExprKind::Call(f, args) => {
+                let f = span_to_string(ctx, f.span);
+                if !is_atomic_intrinsic(&f) || args.is_empty() {
+                    return;
+                }
+                let place = some_or!(atomic_place(ctx, &args[0]), return);
+                let intrinsic = f.rsplit("atomic_").next().unwrap();
+                let (op, success, failure) = some_or!(parse_intrinsic(intrinsic), return);
+                let args: Vec<_> = args[1..]
+                    .iter()
+                    .map(|arg| span_to_string(ctx, arg.span))
+                    .collect();
+                let ordering = |o: &str| format!("std::sync::atomic::Ordering::{}", o);
+                let new_e = match op {
+                    "load" => format!("{}.load({})", place, ordering(success)),
+                    "compare_exchange" | "compare_exchange_weak" => format!(
+                        "match {}.{}({}, {}, {}, {}) {{ Ok(v) => (v, true), Err(v) => (v, false) }}",
+                        place,
+                        op,
+                        args[0],
+                        args[1],
+                        ordering(success),
+                        ordering(failure)
+                    ),
+                    _ => format!("{}.{}({}, {})", place, op, args[0], ordering(success)),
+                };
+                add_replacement(ctx, e.span, new_e);
+            }
+            ExprKind::Path(_) => {
+                let x = some_or!(promoted_of(ctx, e), return);
+                let hir = ctx.tcx.hir();
+                if let Some(Node::Expr(Expr {
+                    kind: ExprKind::AssignOp(_, l, _),
+                    ..
+                })) = hir.find(hir.get_parent_node(e.hir_id))
+                {
+                    if l.hir_id == e.hir_id {
+                        return;
+                    }
+                }
+                let new_e = format!("{}.load(std::sync::atomic::Ordering::SeqCst)", x);
+                add_replacement(ctx, e.span, new_e);
+            }
+            ExprKind::AssignOp(op, lhs, rhs) => {
+                let x = some_or!(promoted_of(ctx, lhs), return);
+                let method = match op.node {
+                    BinOpKind::Add => "fetch_add",
+                    BinOpKind::Sub => "fetch_sub",
+                    _ => return,
+                };
+                let new_e = format!("{}.{}(", x, method);
+                add_replacement(ctx, lhs.span.until(rhs.span), new_e);
+                let new_e = ", std::sync::atomic::Ordering::SeqCst)".to_string();
+                add_replacement(ctx, rhs.span.shrink_to_hi(), new_e);
+            }
+            ExprKind::Assign(lhs, rhs, _) => {
+                let place = some_or!(atomic_place(ctx, lhs), return);
+                let new_e = format!(
+                    "{}.store({}, std::sync::atomic::Ordering::SeqCst)",
+                    place,
+                    span_to_string(ctx, rhs.span)
+                );
+                add_replacement(ctx, e.span, new_e);
+            }
+            ExprKind::Struct(_, fs, _) => {
+                let s = type_to_string(type_of(ctx, e.hir_id));
+                for f in fs.iter() {
+                    let key = format!("{}.{}", s, f.ident.name.to_ident_string());
+                    let ty = some_or!(atomic_map().get(&key), continue);
+                    let init = span_to_string(ctx, f.expr.span);
+                    add_replacement(ctx, f.expr.span, atomic_new(ty, init));
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+/// The retyped global or struct field accessed by `e`.
+fn atomic_place(ctx: &LateContext<'_>, e: &Expr<'_>) -> Option<String> {
+    let e = unwrap_addr(unwrap_cast_recursively(e));
+    match &e.kind {
+        ExprKind::Path(_) => {
+            if !matches!(resolve_path(ctx, e)?, Res::Def(DefKind::Static(_), _)) {
+                return None;
+            }
+        }
+        ExprKind::Field(_, _) => (),
+        _ => return None,
+    }
+    atomic_map()
+        .contains_key(&lock_key(ctx, e)?)
+        .then(|| span_to_string(ctx, e.span))
+}
+
+/// The global promoted from a lock-protected integer to an atomic.
+fn promoted_of(ctx: &LateContext<'_>, e: &Expr<'_>) -> Option<String> {
+    if !matches!(resolve_path(ctx, e)?, Res::Def(DefKind::Static(_), _)) {
+        return None;
+    }
+    let x = span_to_string(ctx, e.span);
+    promoted_map().contains_key(&x).then_some(x)
+}
+
+pub(super) fn atomic_new(ty: &str, init: String) -> String {
+    let ty = ty.split('<').next().unwrap();
+    format!("std::sync::atomic::{}::new({})", ty, init)
+}
+
+/// Splits an intrinsic name such as `cxchg_acq_failrelaxed` or `cxchg_acquire_relaxed` into the
+/// method and orderings; the first ordering is for success and the second for failure, which
+/// cannot release.
+fn parse_intrinsic(intrinsic: &str) -> Option<(&'static str, &'static str, &'static str)> {
+    let mut tokens = intrinsic.split('_');
+    let op = match tokens.next()? {
+        "load" => "load",
+        "store" => "store",
+        "xchg" => "swap",
+        "xadd" => "fetch_add",
+        "xsub" => "fetch_sub",
+        "and" => "fetch_and",
+        "nand" => "fetch_nand",
+        "or" => "fetch_or",
+        "xor" => "fetch_xor",
+        "max" | "umax" => "fetch_max",
+        "min" | "umin" => "fetch_min",
+        "cxchg" => "compare_exchange",
+        "cxchgweak" => "compare_exchange_weak",
+        _ => return None,
+    };
+    let ordering = |o: &str| match o {
+        "" | "seqcst" => Some("SeqCst"),
+        "acq" | "acquire" => Some("Acquire"),
+        "rel" | "release" => Some("Release"),
+        "acqrel" => Some("AcqRel"),
+        "relaxed" => Some("Relaxed"),
+        _ => None,
+    };
+    let tokens: Vec<_> = tokens.collect();
+    // older names omit a sequentially consistent success ordering, as in `cxchg_failrelaxed`
+    let (success, failure) = match tokens[..] {
+        [] => ("SeqCst", None),
+        [f] if f.starts_with("fail") => ("SeqCst", Some(f)),
+        [s] => (ordering(s)?, None),
+        [s, f] => (ordering(s)?, Some(f)),
+        _ => return None,
+    };
+    let failure = match failure {
+        Some(f) => match ordering(f.strip_prefix("fail").unwrap_or(f))? {
+            "Release" | "AcqRel" => return None,
+            o => o,
+        },
+        None => match success {
+            "AcqRel" => "Acquire",
+            "Release" => "Relaxed",
+            o => o,
+        },
+    };
+    Some((op, success, failure))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::parse_intrinsic;
+
+    #[test]
+    fn test_parse_intrinsic() {
+        // the method and the success and failure orderings; empty if rejected
+        let cases = [
+            ("load", "load SeqCst SeqCst"),
+            ("load_acquire", "load Acquire Acquire"),
+            ("store_rel", "store Release Relaxed"),
+            ("xadd_relaxed", "fetch_add Relaxed Relaxed"),
+            ("cxchg", "compare_exchange SeqCst SeqCst"),
+            ("cxchg_acqrel", "compare_exchange AcqRel Acquire"),
+            ("cxchg_failrelaxed", "compare_exchange SeqCst Relaxed"),
+            ("cxchg_acq_failrelaxed", "compare_exchange Acquire Relaxed"),
+            ("cxchg_acquire_relaxed", "compare_exchange Acquire Relaxed"),
+            ("cxchg_relaxed_seqcst", "compare_exchange Relaxed SeqCst"),
+            ("cxchgweak_relaxed", "compare_exchange_weak Relaxed Relaxed"),
+            ("cxchg_acquire_release", ""),
+            ("cxchg_acqrel_failacqrel", ""),
+            ("cxchg_seqcst_seqcst_seqcst", ""),
+            ("cxchg_failacq_relaxed", ""),
+            ("cxchg_consume", ""),
+            ("fence_seqcst", ""),
+        ];
+        for (intrinsic, expected) in cases {
+            let parsed = parse_intrinsic(intrinsic)
+                .map(|(op, success, failure)| format!("{} {} {}", op, success, failure))
+                .unwrap_or_default();
+            assert_eq!(parsed, expected, "{}", intrinsic);
+        }
+    }
+}
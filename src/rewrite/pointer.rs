@@ -0,0 +1,110 @@
+use std::collections::BTreeMap;
+
+use rustc_hir::{Expr, ExprKind, UnOp};
+use rustc_lint::LateContext;
+
+use crate::util::{
+    expr_to_path, span_to_string, type_of, unwrap_cast_recursively, ExprPath, ExprPathProj,
+};
+
+/// How the memory behind a lock-protected raw pointer is held, judged from the values assigned
+/// to the pointer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum PointerClass {
+    /// only assigned buffers returned by allocation functions
+    Owned,
+    /// only assigned addresses of places
+    Borrowed,
+    /// assigned other pointers or copied out, so it may alias memory reached without the lock
+    Shared,
+}
+
+impl PointerClass {
+    fn join(self, other: Self) -> Self {
+        if self == other {
+            self
+        } else {
+            Self::Shared
+        }
+    }
+
+    /// Why moving the pointer to another thread together with its lock is sound; `None` for
+    /// shared pointers, for which it is not.
+    pub(super) fn justification(&self) -> Option<&'static str> {
+        match self {
+            Self::Owned => Some("owns a heap buffer that is reached only through the lock"),
+            Self::Borrowed => {
+                Some("borrows memory that outlives the lock and is not freed through it")
+            }
+            Self::Shared => None,
+        }
+    }
+}
+
+/// The class of a value assigned to a raw pointer; `None` for null pointers, which are compatible
+/// with every class.
+pub(super) fn pointer_source(ctx: &LateContext<'_>, rhs: &Expr<'_>) -> Option<PointerClass> {
+    let rhs = unwrap_cast_recursively(rhs);
+    match &rhs.kind {
+        ExprKind::Lit(_) => None,
+        ExprKind::Call(f, _) => match span_to_string(ctx, f.span).as_str() {
+            "malloc" | "calloc" | "realloc" | "strdup" => Some(PointerClass::Owned),
+            "std::ptr::null"
+            | "std::ptr::null_mut"
+            | "::std::ptr::null"
+            | "::std::ptr::null_mut" => None,
+            _ => Some(PointerClass::Shared),
+        },
+        ExprKind::AddrOf(_, _, _) => Some(PointerClass::Borrowed),
+        _ => Some(PointerClass::Shared),
+    }
+}
+
+/// The raw pointer read by `e`, whose value is copied out, e.g., `q = s->buf`.
+pub(super) fn copied_pointer(ctx: &LateContext<'_>, e: &Expr<'_>) -> Option<ExprPath> {
+    let e = unwrap_cast_recursively(e);
+    if !type_of(ctx, e.hir_id).is_unsafe_ptr() {
+        return None;
+    }
+    match &e.kind {
+        ExprKind::Path(_)
+        | ExprKind::Field(_, _)
+        | ExprKind::Index(_, _)
+        | ExprKind::Unary(UnOp::Deref, _) => expr_to_path(ctx, e),
+        _ => None,
+    }
+}
+
+/// Classifies raw pointers by the global (`x`) or struct field (`T.f`) holding them, from the
+/// assignments `(function, lhs, class)`; the struct types are those of the paths in each
+/// function.
+pub(super) fn classify_pointers(
+    assigns: &[(String, ExprPath, Option<PointerClass>)],
+    path_type_map: &BTreeMap<ExprPath, BTreeMap<String, String>>,
+) -> BTreeMap<String, PointerClass> {
+    let mut map: BTreeMap<String, PointerClass> = BTreeMap::new();
+    for (func, path, class) in assigns {
+        let key = if path.is_variable() {
+            path.base.clone()
+        } else {
+            let mut prefix = path.clone();
+            match prefix.pop() {
+                // elements of arrays
+                Some(ExprPathProj::Index(_)) if prefix.is_variable() => prefix.base,
+                Some(ExprPathProj::Field(f)) => {
+                    let ty = path_type_map.get(&prefix).and_then(|m| m.get(func));
+                    match ty {
+                        Some(ty) => format!("{}.{}", ty, f),
+                        None => continue,
+                    }
+                }
+                _ => continue,
+            }
+        };
+        if let Some(c) = class {
+            let c = map.get(&key).map_or(*c, |c0| c0.join(*c));
+            map.insert(key, c);
+        }
+    }
+    map
+}
@@ -8,18 +8,21 @@ use std::{
 };
 
 use etrace::some_or;
-use rustc_hir::{def::Res, BodyId, Expr, ExprKind, HirId, ItemKind, Node, UnOp, VariantData};
+use rustc_hir::{def::Res, BodyId, Expr, ExprKind, HirId, Item, ItemKind, Node, UnOp, VariantData};
 use rustc_index::vec::Idx;
 use rustc_lint::{LateContext, LintContext};
-use rustc_middle::ty::{Ty, TyCtxt, TyKind, TypeAndMut, TypeckResults};
+use rustc_middle::ty::{IntTy, Ty, TyCtxt, TyKind, TypeAndMut, TypeckResults, UintTy};
 use rustc_mir_dataflow::fmt::DebugWithContext;
-use rustc_span::{def_id::DefId, Span};
+use rustc_span::{def_id::DefId, sym, Span};
 use serde::{
     de::{Error, Visitor},
     Deserialize, Serialize,
 };
 
-use crate::dataflow::Arg;
+use crate::{
+    config::{lock_api, lock_type, lock_type_names, LockApi},
+    dataflow::Arg,
+};
 
 #[derive(Clone, Copy, Eq, PartialEq, Debug, Hash, PartialOrd, Ord)]
 #[repr(transparent)]
@@ -349,7 +352,7 @@ fn find_delimiter(s: &str) -> Option<(&str, &str, bool)> {
 
 #[cfg(test)]
 mod tests {
-    use super::{ExprPath, ExprPathProj};
+    use super::{normalize_type, ExprPath, ExprPathProj};
 
     #[test]
     fn test1() {
@@ -418,6 +421,21 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_normalize_type() {
+        let n = |t: &str| normalize_type(t.to_string());
+        assert_eq!(n("mtx_t"), "pthread_mutex_t");
+        assert_eq!(n("*mut cnd_t"), "*mut pthread_cond_t");
+        assert_eq!(n("[mtx_t; 10]"), "[pthread_mutex_t; 10]");
+        assert_eq!(
+            n("__anonunion_mtx_t_1019"),
+            "__anonunion_pthread_mutex_t_1019"
+        );
+        assert_eq!(n("my_mtx_t"), "my_mtx_t");
+        assert_eq!(n("__anonstruct_mtx_t_x"), "__anonstruct_mtx_t_x");
+        assert_eq!(n("pthread_mutex_t"), "pthread_mutex_t");
+    }
 }
 
 pub fn compile_args(input: &Path, dep: &Path) -> Vec<String> {
@@ -526,13 +544,22 @@ pub fn typeck<'tcx>(ctx: &LateContext<'tcx>) -> Option<&'tcx TypeckResults<'tcx>
 
 pub fn resolve_path(ctx: &LateContext<'_>, expr: &Expr<'_>) -> Option<Res> {
     if let ExprKind::Path(p) = &expr.kind {
-        let typeck_res = some_or!(typeck(ctx), return None);
+        // visitors run from `check_crate` have no enclosing body
+        let typeck_res = typeck(ctx).unwrap_or_else(|| ctx.tcx.typeck(expr.hir_id.owner));
         Some(typeck_res.qpath_res(p, expr.hir_id))
     } else {
         None
     }
 }
 
+pub fn is_thread_local(ctx: &LateContext<'_>, i: &Item<'_>) -> bool {
+    ctx.tcx
+        .hir()
+        .attrs(i.hir_id())
+        .iter()
+        .any(|a| a.has_name(sym::thread_local))
+}
+
 pub fn resolve_struct(ctx: &LateContext<'_>, ty: Ty<'_>) -> Option<BTreeMap<String, String>> {
     match ctx.tcx.hir().get_if_local(ty.ty_adt_def()?.did())? {
         Node::Item(i) => match &i.kind {
@@ -612,6 +639,27 @@ pub fn expr_to_path(ctx: &LateContext<'_>, expr: &Expr<'_>) -> Option<ExprPath>
     }
 }
 
+/// Identifies a lock by the global variable (`m`) or the struct field (`T.m`) holding it.
+pub fn lock_key(ctx: &LateContext<'_>, expr: &Expr<'_>) -> Option<String> {
+    match &expr.kind {
+        ExprKind::Cast(e, _) | ExprKind::DropTemps(e) | ExprKind::AddrOf(_, _, e) => {
+            lock_key(ctx, e)
+        }
+        ExprKind::Index(e, _) => lock_key(ctx, e),
+        ExprKind::MethodCall(m, args, _) => match m.ident.to_string().as_str() {
+            "offset" | "as_mut_ptr" => lock_key(ctx, &args[0]),
+            _ => None,
+        },
+        ExprKind::Unary(UnOp::Deref, e) => lock_key(ctx, e),
+        ExprKind::Field(e, f) => {
+            let ty = type_to_string(unwrap_ptr_from_type(type_of(ctx, e.hir_id)));
+            Some(format!("{}.{}", ty, f))
+        }
+        ExprKind::Path(_) => Some(span_to_string(ctx, expr.span)),
+        _ => None,
+    }
+}
+
 pub fn type_of<'a, 'b>(ctx: &'a LateContext<'b>, hir_id: HirId) -> Ty<'b> {
     ctx.tcx.typeck(hir_id.owner).node_type(hir_id)
 }
@@ -623,10 +671,113 @@ pub fn unwrap_ptr_from_type(ty: Ty<'_>) -> Ty<'_> {
     }
 }
 
+pub fn is_atomic_intrinsic(f: &str) -> bool {
+    f.contains("intrinsics::atomic_")
+}
+
+/// The `std::sync::atomic` type replacing a place of the given type.
+pub fn atomic_type(ty: Ty<'_>) -> Option<String> {
+    let ty = match ty.kind() {
+        TyKind::Bool => "AtomicBool",
+        TyKind::Int(IntTy::I8) => "AtomicI8",
+        TyKind::Int(IntTy::I16) => "AtomicI16",
+        TyKind::Int(IntTy::I32) => "AtomicI32",
+        TyKind::Int(IntTy::I64) => "AtomicI64",
+        TyKind::Int(IntTy::Isize) => "AtomicIsize",
+        TyKind::Uint(UintTy::U8) => "AtomicU8",
+        TyKind::Uint(UintTy::U16) => "AtomicU16",
+        TyKind::Uint(UintTy::U32) => "AtomicU32",
+        TyKind::Uint(UintTy::U64) => "AtomicU64",
+        TyKind::Uint(UintTy::Usize) => "AtomicUsize",
+        TyKind::RawPtr(TypeAndMut { ty, .. }) => {
+            return Some(format!("AtomicPtr<{}>", type_to_string(*ty)))
+        }
+        _ => return None,
+    };
+    Some(ty.to_string())
+}
+
 pub fn type_to_string(ty: Ty<'_>) -> String {
     ty.to_string().replace("main::", "")
 }
 
+const C11_TYPES: [(&str, &str); 2] = [("mtx_t", "pthread_mutex_t"), ("cnd_t", "pthread_cond_t")];
+
+/// Names C11 `<threads.h>` types, including the `__anonunion_mtx_t_N` names given by CIL, and
+/// user-declared lock types after their pthread counterparts, so that they are recognized alike.
+pub fn normalize_type(ty: String) -> String {
+    if C11_TYPES.iter().all(|(c11, _)| !ty.contains(c11))
+        && lock_type_names().all(|t| !ty.contains(t.as_str()))
+    {
+        return ty;
+    }
+    let normalize_token = |token: &str| {
+        if let Some(posix) = lock_type(token) {
+            return posix.to_string();
+        }
+        for (c11, posix) in C11_TYPES {
+            if token == c11 {
+                return posix.to_string();
+            }
+            for prefix in ["__anonunion_", "__anonstruct_"] {
+                let suffix = token
+                    .strip_prefix(prefix)
+                    .and_then(|t| t.strip_prefix(c11))
+                    .and_then(|t| t.strip_prefix('_'));
+                if let Some(n) = suffix {
+                    if !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()) {
+                        return format!("{}{}_{}", prefix, posix, n);
+                    }
+                }
+            }
+        }
+        token.to_string()
+    };
+    let mut res = String::new();
+    let mut token = String::new();
+    for c in ty.chars() {
+        if c.is_alphanumeric() || c == '_' {
+            token.push(c);
+        } else {
+            res.push_str(&normalize_token(&token));
+            res.push(c);
+            token.clear();
+        }
+    }
+    res.push_str(&normalize_token(&token));
+    res
+}
+
+/// The pthread function performing the same operation as a C11 `<threads.h>` function.
+pub fn c11_function(f: &str) -> Option<&'static str> {
+    let f = match f {
+        "mtx_init" => "pthread_mutex_init",
+        "mtx_lock" => "pthread_mutex_lock",
+        "mtx_trylock" => "pthread_mutex_trylock",
+        "mtx_timedlock" => "pthread_mutex_timedlock",
+        "mtx_unlock" => "pthread_mutex_unlock",
+        "mtx_destroy" => "pthread_mutex_destroy",
+        "cnd_init" => "pthread_cond_init",
+        "cnd_signal" => "pthread_cond_signal",
+        "cnd_broadcast" => "pthread_cond_broadcast",
+        "cnd_wait" => "pthread_cond_wait",
+        "cnd_timedwait" => "pthread_cond_timedwait",
+        "cnd_destroy" => "pthread_cond_destroy",
+        "thrd_create" => "pthread_create",
+        "thrd_join" => "pthread_join",
+        "thrd_detach" => "pthread_detach",
+        _ => return None,
+    };
+    Some(f)
+}
+
+/// The pthread function a call to `f` is treated as, covering C11 and user-declared lock APIs.
+pub fn pthread_function(f: &str) -> Option<&'static str> {
+    lock_api(f)
+        .map(LockApi::pthread_name)
+        .or_else(|| c11_function(f))
+}
+
 pub fn join(mut v: Vec<String>, sep: &str) -> String {
     v.drain(..).intersperse(sep.to_string()).collect()
 }